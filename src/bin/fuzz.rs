@@ -1,12 +1,134 @@
 extern crate brdgme_game;
-extern crate brdgme_rand_bot;
 extern crate lords_of_vegas;
+extern crate rand;
+extern crate serde_json;
 
-use lords_of_vegas::Game;
-use brdgme_rand_bot::fuzz;
+use brdgme_game::Gamer;
 
-use std::io::stdout;
+use lords_of_vegas::{Command, Game};
+use lords_of_vegas::casino::CASINOS;
+use lords_of_vegas::replay::{state_hash, Replay};
 
+use rand::Rng;
+
+use std::process;
+
+/// The most actions a player takes before ending their turn, so games keep moving forward.
+const MAX_ACTIONS_PER_TURN: usize = 3;
+
+/// How many random inputs are tried before each legal command.
+const RANDOM_INPUTS_PER_COMMAND: usize = 2;
+
+/// Words which random input is built from, including locations and numbers which are out of
+/// range.
+static WORDS: &'static [&'static str] = &[
+    "build", "sprawl", "remodel", "reorg", "gamble", "raise", "roll", "done", "A1", "B6", "C12",
+    "F9", "A0", "A13", "G1", "Z", "0", "1", "5", "-1", "99999999999999999999", "", " ", "é",
+];
+
+/// Plays random games forever, half of them with physical dice, validating the game after every
+/// command and printing a replay of the first game which breaks. Each command from
+/// `legal_commands` is preceded by random input from random players, which must either run or
+/// fail without changing the game.
 fn main() {
-    fuzz::<Game, _>(&mut stdout());
+    let mut rng = rand::thread_rng();
+    let mut games = 0;
+    loop {
+        let seed: u64 = rng.gen();
+        let players = rng.gen_range(2, 7);
+        let mut replay = if rng.gen_weighted_bool(2) {
+            Replay::physical(seed, players)
+        } else {
+            Replay::new(seed, players)
+        };
+        let mut game = replay.start().expect("could not create game");
+        check(&game, &replay);
+        let mut actions = 0;
+        while !game.finished {
+            for _ in 0..RANDOM_INPUTS_PER_COMMAND {
+                for p in 0..players {
+                    game.command_spec(p);
+                }
+                let p = rng.gen_range(0, players);
+                let input = random_input(&mut rng, &game.legal_commands(game.current_player));
+                let before = state_hash(&game);
+                if replay.record(&mut game, p, &input).is_err() && state_hash(&game) != before {
+                    fail(&format!("failed input {:?} changed the game", input), &replay);
+                }
+                check(&game, &replay);
+                if game.finished {
+                    break;
+                }
+            }
+            if game.finished {
+                continue;
+            }
+            let p = game.current_player;
+            let legal = game.legal_commands(p);
+            let command = if actions >= MAX_ACTIONS_PER_TURN && legal.contains(&Command::Done) {
+                Command::Done
+            } else {
+                match rng.choose(&legal) {
+                    Some(c) => c.clone(),
+                    None => fail(&format!("player {} has no legal commands", p), &replay),
+                }
+            };
+            actions = if command == Command::Done { 0 } else { actions + 1 };
+            if let Err(e) = replay.record(&mut game, p, &command.to_string()) {
+                fail(&format!("legal command {} failed: {}", command, e), &replay);
+            }
+            check(&game, &replay);
+        }
+        games += 1;
+        if games % 100 == 0 {
+            println!("{} games played", games);
+        }
+    }
+}
+
+/// Builds input which is usually invalid: a legal command cut short or with extra words, words
+/// from `WORDS` and casino names, or random characters.
+fn random_input<R: Rng>(rng: &mut R, legal: &[Command]) -> String {
+    match (rng.gen_range(0, 4), rng.choose(legal)) {
+        (0, Some(c)) => {
+            let input = c.to_string();
+            let cut = rng.gen_range(0, input.len() + 1);
+            input[..cut].to_string()
+        }
+        (1, Some(c)) => format!("{} {}", c, rng.choose(WORDS).unwrap()),
+        (2, _) | (_, None) => {
+            let len = rng.gen_range(1, 5);
+            (0..len)
+                .map(|_| {
+                    if rng.gen_weighted_bool(4) {
+                        rng.choose(CASINOS).unwrap().to_string()
+                    } else {
+                        rng.choose(WORDS).unwrap().to_string()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        }
+        _ => {
+            let len = rng.gen_range(0, 20);
+            rng.gen_ascii_chars().take(len).collect()
+        }
+    }
+}
+
+fn check(game: &Game, replay: &Replay) {
+    let violations = game.validate();
+    if !violations.is_empty() {
+        let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        fail(&messages.join("\n"), replay);
+    }
+}
+
+fn fail(message: &str, replay: &Replay) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "{}",
+        serde_json::to_string(replay).expect("could not serialise replay")
+    );
+    process::exit(1);
 }
@@ -0,0 +1,39 @@
+extern crate lords_of_vegas;
+extern crate serde_json;
+
+use lords_of_vegas::replay::{state_hash, Replay};
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+
+fn main() {
+    let mut input = String::new();
+    let read = match env::args().nth(1) {
+        Some(path) => File::open(&path).and_then(|mut f| f.read_to_string(&mut input)),
+        None => io::stdin().read_to_string(&mut input),
+    };
+    if let Err(e) = read {
+        eprintln!("could not read replay: {}", e);
+        process::exit(2);
+    }
+    let replay: Replay = match serde_json::from_str(&input) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("could not parse replay: {}", e);
+            process::exit(2);
+        }
+    };
+    match replay.verify() {
+        Ok(game) => println!(
+            "replay verified, {} commands, final state hash {}",
+            replay.commands.len(),
+            state_hash(&game)
+        ),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -1,20 +1,34 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error as DeError, Unexpected, Visitor};
 
-use brdgme_game::Log;
 use brdgme_markup::Node as N;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
-use std::iter::FromIterator;
 use std::convert::TryFrom;
 
 use casino::Casino;
-use tile::TILES;
+use tile::LAYOUT;
+use dice::Dice;
+use supply::SupplyError;
+use event::GameEvent;
+use {CASINO_TILES, PLAYER_DICE, PLAYER_OWNER_TOKENS};
 
-use roll;
+pub const BLOCK_WIDTH: usize = 3;
+pub const MAX_BOSS_TIE_PASSES: usize = 10;
 
-const BLOCK_WIDTH: usize = 3;
+lazy_static! {
+    /// Neighbour indices for every lot index, so neighbour lookups don't need any arithmetic.
+    static ref NEIGHBOURS: Vec<Vec<usize>> = (0..lot_count())
+        .map(|i| {
+            Loc::from_index(i)
+                .adjacent_lots()
+                .iter()
+                .map(|n| n.index().expect("neighbour should be on the board"))
+                .collect()
+        })
+        .collect();
+}
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Block {
@@ -30,12 +44,58 @@ pub static BLOCKS: &'static [Block] = &[Block::A, Block::B, Block::C, Block::D,
 
 impl Block {
     pub fn max_lot(&self) -> Lot {
+        LAYOUT.lots(self)
+    }
+
+    /// The index of the block's first lot in the board's tile storage.
+    pub fn offset(&self) -> usize {
+        LAYOUT.offset(self)
+    }
+
+    pub fn side(&self) -> Side {
+        LAYOUT.block(self).side
+    }
+
+    /// The tile row of the top of the block, counting from the top of the board.
+    pub fn row(&self) -> usize {
+        LAYOUT.block(self).row
+    }
+
+    /// The number of alleys between the top of the board and the block.
+    pub fn alleys(&self) -> usize {
+        LAYOUT.block(self).alleys
+    }
+
+    pub fn rows(&self) -> usize {
+        LAYOUT.block(self).rows()
+    }
+}
+
+/// Which side of the Strip a block is on. The Strip runs down the middle of the board between
+/// the two columns of blocks.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    /// The column of lots in a block which faces the Strip.
+    pub fn strip_col(&self) -> usize {
         match *self {
-            Block::A | Block::B | Block::E => 6,
-            Block::C => 12,
-            Block::D | Block::F => 9,
+            Side::Left => BLOCK_WIDTH,
+            Side::Right => 1,
         }
     }
+
+    pub fn is_strip_lot(&self, lot: Lot) -> bool {
+        (lot - 1) % BLOCK_WIDTH + 1 == self.strip_col()
+    }
+}
+
+/// The number of lots on the board across all blocks.
+pub fn lot_count() -> usize {
+    LAYOUT.lot_count()
 }
 
 impl TryFrom<char> for Block {
@@ -92,6 +152,9 @@ impl<'a> TryFrom<&'a str> for Loc {
         let lot: Lot = lot_str
             .parse()
             .map_err(|_| "Loc lot must be a number".to_string())?;
+        if lot == 0 {
+            return Err("Loc lot must be at least 1".to_string());
+        }
         Ok((block, lot).into())
     }
 }
@@ -103,7 +166,65 @@ impl From<(Block, Lot)> for Loc {
 }
 
 impl Loc {
+    /// The index of the loc in the board's tile storage, or `None` if the lot isn't in its block.
+    pub fn index(&self) -> Option<usize> {
+        if self.lot < 1 || self.lot > self.block.max_lot() {
+            return None;
+        }
+        Some(self.block.offset() + self.lot - 1)
+    }
+
+    /// The loc at an index in the board's tile storage.
+    pub fn from_index(index: usize) -> Loc {
+        let block = *BLOCKS
+            .iter()
+            .rev()
+            .find(|b| b.offset() <= index)
+            .expect("index should be in a block");
+        (block, index - block.offset() + 1).into()
+    }
+
+    /// The column of the loc within its block, starting at 1.
+    pub fn col(&self) -> usize {
+        (self.lot - 1) % BLOCK_WIDTH + 1
+    }
+
+    /// The tile row of the loc, counting from the top of the board.
+    pub fn row(&self) -> usize {
+        self.block.row() + (self.lot - 1) / BLOCK_WIDTH
+    }
+
+    /// Whether the loc faces the Strip.
+    pub fn on_strip(&self) -> bool {
+        self.block.side().is_strip_lot(self.lot)
+    }
+
+    /// The Strip lots directly opposite this one on the other side of the Strip, which is empty
+    /// if the loc isn't on the Strip or nothing faces it.
+    pub fn across_strip(&self) -> Vec<Loc> {
+        if !self.on_strip() {
+            return vec![];
+        }
+        let side = self.block.side();
+        let row = self.row();
+        BLOCKS
+            .iter()
+            .filter(|b| b.side() != side && row >= b.row() && row < b.row() + b.rows())
+            .map(|b| Loc {
+                block: *b,
+                lot: (row - b.row()) * BLOCK_WIDTH + b.side().strip_col(),
+            })
+            .collect()
+    }
+
     pub fn neighbours(&self) -> Vec<Loc> {
+        match self.index() {
+            Some(i) => NEIGHBOURS[i].iter().map(|&n| Loc::from_index(n)).collect(),
+            None => vec![],
+        }
+    }
+
+    fn adjacent_lots(&self) -> Vec<Loc> {
         let mut n: Vec<Loc> = vec![];
         if self.lot > BLOCK_WIDTH {
             n.push((self.block, self.lot - BLOCK_WIDTH).into());
@@ -193,8 +314,58 @@ impl Default for BoardTile {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Board(HashMap<Loc, BoardTile>);
+/// Built tiles join into a casino with neighbours of the same casino and height.
+fn casino_key(bt: &BoardTile) -> Option<(Casino, usize)> {
+    match *bt {
+        BoardTile::Built { casino, height, .. } => Some((casino, height)),
+        _ => None,
+    }
+}
+
+/// The tiles on the board, indexed by `Loc::index`, along with which casino each built tile
+/// belongs to. The casino index is kept up to date by `set`, so casinos can be looked up without
+/// flooding the board.
+#[derive(Debug, Clone)]
+pub struct Board {
+    tiles: Vec<BoardTile>,
+    /// For each lot, the lowest lot index of the casino it is part of.
+    components: Vec<Option<usize>>,
+}
+
+// The board is serialised as a map from location to tile, leaving out unowned lots. Tiles are
+// written in location order so the same board always gives the same JSON, which replays rely on
+// when hashing game state.
+impl Serialize for Board {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        let tiles: BTreeMap<Loc, BoardTile> = self.tiles()
+            .filter(|&(_, bt)| match bt {
+                BoardTile::Unowned => false,
+                _ => true,
+            })
+            .collect();
+        serializer.serialize_newtype_struct("Board", &tiles)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D>(deserializer: D) -> Result<Board, D::Error>
+        where
+            D: Deserializer<'de>,
+    {
+        let tiles: BTreeMap<Loc, BoardTile> = Deserialize::deserialize(deserializer)?;
+        let mut board = Board::default();
+        for (loc, bt) in tiles {
+            let i = loc.index()
+                .ok_or_else(|| D::Error::custom(format!("{} isn't on the board", loc)))?;
+            board.tiles[i] = bt;
+        }
+        board.rebuild_components();
+        Ok(board)
+    }
+}
 
 #[derive(Default, Copy, Clone)]
 pub struct UsedResources {
@@ -204,16 +375,99 @@ pub struct UsedResources {
 
 impl Board {
     pub fn get(&self, loc: &Loc) -> BoardTile {
-        self.0.get(loc).cloned().unwrap_or_default()
+        loc.index().map(|i| self.tiles[i]).unwrap_or_default()
     }
 
     pub fn set(&mut self, loc: Loc, bt: BoardTile) {
-        self.0.insert(loc, bt);
+        let i = loc.index()
+            .unwrap_or_else(|| panic!("{} isn't on the board", loc));
+        let old = self.tiles[i];
+        self.tiles[i] = bt;
+        // Changing the owner or die of a tile doesn't change which casino it's in.
+        if casino_key(&old) != casino_key(&bt) {
+            self.update_components(i);
+        }
+        debug_assert!(
+            self.components_match_flood(),
+            "casino index is out of date after setting {}",
+            loc
+        );
+    }
+
+    /// Relabels the casinos touching a changed lot, which covers casinos merging when a tile is
+    /// built next to them and splitting when a tile is recoloured or raised.
+    fn update_components(&mut self, changed: usize) {
+        let mut affected: Vec<usize> = vec![changed];
+        affected.extend(NEIGHBOURS[changed].iter().cloned());
+        let roots: Vec<usize> = affected
+            .iter()
+            .filter_map(|&i| self.components[i])
+            .collect();
+        for c in self.components.iter_mut() {
+            if c.map(|r| roots.contains(&r)).unwrap_or(false) {
+                *c = None;
+            }
+        }
+        self.label_components();
+    }
+
+    fn rebuild_components(&mut self) {
+        self.components = vec![None; lot_count()];
+        self.label_components();
+    }
+
+    /// Labels every built tile which doesn't yet have a casino.
+    fn label_components(&mut self) {
+        for i in 0..lot_count() {
+            if self.components[i].is_some() || casino_key(&self.tiles[i]).is_none() {
+                continue;
+            }
+            let members = self.flood(i);
+            let root = *members.iter().min().expect("flood should include the start");
+            for m in members {
+                self.components[m] = Some(root);
+            }
+        }
+    }
+
+    /// Finds the lot indices of the casino containing a built tile by flooding out from it.
+    fn flood(&self, start: usize) -> Vec<usize> {
+        let key = casino_key(&self.tiles[start]);
+        let mut queue: Vec<usize> = vec![start];
+        let mut visited = vec![false; lot_count()];
+        visited[start] = true;
+        let mut members: Vec<usize> = vec![];
+        while let Some(next) = queue.pop() {
+            members.push(next);
+            for &n in &NEIGHBOURS[next] {
+                if !visited[n] && casino_key(&self.tiles[n]) == key {
+                    visited[n] = true;
+                    queue.push(n);
+                }
+            }
+        }
+        members
+    }
+
+    /// Checks the casino index against casinos found by flooding the board.
+    fn components_match_flood(&self) -> bool {
+        (0..lot_count()).all(|i| match casino_key(&self.tiles[i]) {
+            None => self.components[i].is_none(),
+            Some(_) => self.components[i] == self.flood(i).into_iter().min(),
+        })
+    }
+
+    /// Every lot on the board in location order.
+    pub fn tiles<'a>(&'a self) -> impl Iterator<Item = (Loc, BoardTile)> + 'a {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(i, bt)| (Loc::from_index(i), *bt))
     }
 
     pub fn used_resources(&self, p: usize) -> UsedResources {
         let mut used = UsedResources::default();
-        for bt in self.0.values() {
+        for bt in &self.tiles {
             match *bt {
                 BoardTile::Owned { player } if player == p => used.tokens += 1,
                 BoardTile::Built {
@@ -229,18 +483,55 @@ impl Board {
         used
     }
 
+    pub fn remaining_dice(&self, p: usize) -> usize {
+        PLAYER_DICE.saturating_sub(self.used_resources(p).dice)
+    }
+
+    pub fn remaining_owner_tokens(&self, p: usize) -> usize {
+        PLAYER_OWNER_TOKENS.saturating_sub(self.used_resources(p).tokens)
+    }
+
+    pub fn remaining_casino_tiles(&self, c: &Casino) -> usize {
+        CASINO_TILES.saturating_sub(self.casino_tile_count(c))
+    }
+
+    pub fn check_dice(&self, p: usize) -> Result<(), SupplyError> {
+        if self.remaining_dice(p) == 0 {
+            return Err(SupplyError::Dice { player: p });
+        }
+        Ok(())
+    }
+
+    pub fn check_owner_tokens(&self, p: usize) -> Result<(), SupplyError> {
+        if self.remaining_owner_tokens(p) == 0 {
+            return Err(SupplyError::OwnerTokens { player: p });
+        }
+        Ok(())
+    }
+
+    pub fn check_casino_tiles(&self, c: &Casino, needed: usize) -> Result<(), SupplyError> {
+        let remaining = self.remaining_casino_tiles(c);
+        if remaining < needed {
+            return Err(SupplyError::CasinoTiles {
+                casino: *c,
+                needed,
+                remaining,
+            });
+        }
+        Ok(())
+    }
+
     pub fn casino_tile_count(&self, c: &Casino) -> usize {
-        self.0.iter().fold(0, |acc, (_, bt)| match *bt {
+        self.tiles.iter().fold(0, |acc, bt| match *bt {
             BoardTile::Built { casino, .. } if casino == *c => acc + 1,
             _ => acc,
         })
     }
 
     pub fn player_locs(&self, p: usize) -> Vec<Loc> {
-        self.0
-            .iter()
-            .filter_map(|(l, bt)| match *bt {
-                BoardTile::Owned { player } if player == p => Some(*l),
+        self.tiles()
+            .filter_map(|(l, bt)| match bt {
+                BoardTile::Owned { player } if player == p => Some(l),
                 _ => None,
             })
             .collect()
@@ -251,34 +542,19 @@ impl Board {
             BoardTile::Built { casino, height, .. } => (casino, height),
             _ => return None,
         };
-
-        let mut queue: HashSet<Loc> = HashSet::new();
-        queue.insert(*loc);
-        let mut visited: HashSet<Loc> = HashSet::new();
-        let mut tiles: Vec<CasinoTile> = vec![];
-
-        while !queue.is_empty() {
-            let next = *queue.iter().next().expect("queue shouldn't be empty");
-            visited.insert(next);
-            queue.remove(&next);
-            match self.get(&next) {
-                BoardTile::Built {
-                    casino: c,
-                    owner,
-                    height: h,
-                } if c == casino && h == height =>
-                    {
-                        tiles.push(CasinoTile { loc: next, owner });
-                        for n in next.neighbours() {
-                            if !visited.contains(&n) {
-                                queue.insert(n);
-                            }
-                        }
-                    }
-                _ => {}
-            }
-        }
-
+        let root = self.components[loc.index().expect("built tiles should be on the board")];
+        // Tiles are collected in index order, which is also location order, so anything
+        // iterating them is deterministic.
+        let tiles: Vec<CasinoTile> = (0..lot_count())
+            .filter(|&i| self.components[i] == root)
+            .map(|i| CasinoTile {
+                loc: Loc::from_index(i),
+                owner: match self.tiles[i] {
+                    BoardTile::Built { owner, .. } => owner,
+                    _ => None,
+                },
+            })
+            .collect();
         Some(BoardCasino {
             casino,
             height,
@@ -287,21 +563,34 @@ impl Board {
     }
 
     pub fn casinos(&self) -> Vec<BoardCasino> {
-        let mut visited: HashSet<Loc> = HashSet::new();
-        let mut casinos: Vec<BoardCasino> = vec![];
-        for loc in TILES.keys() {
-            if visited.contains(loc) {
-                continue;
-            }
-            if let Some(bc) = self.casino_at(loc) {
-                visited.extend(bc.tiles.iter().map(|t| t.loc));
-                casinos.push(bc);
+        // Each casino is labelled by its lowest lot index, so each label is found exactly once.
+        (0..lot_count())
+            .filter(|&i| self.components[i] == Some(i))
+            .filter_map(|i| self.casino_at(&Loc::from_index(i)))
+            .collect()
+    }
+
+    pub fn boss_casinos(&self, p: usize) -> Vec<BoardCasino> {
+        self.casinos()
+            .into_iter()
+            .filter(|bc| bc.boss() == Some(p))
+            .collect()
+    }
+
+    /// Empty lots adjacent to a casino which it could sprawl to.
+    pub fn sprawl_locs(&self, bc: &BoardCasino) -> Vec<Loc> {
+        let mut locs: HashSet<Loc> = HashSet::new();
+        for t in &bc.tiles {
+            for n in t.loc.neighbours() {
+                if let BoardTile::Unowned = self.get(&n) {
+                    locs.insert(n);
+                }
             }
         }
-        casinos
+        locs.into_iter().collect()
     }
 
-    pub fn reroll_at(&mut self, loc: &Loc) -> Option<usize> {
+    pub fn reroll_at<D: Dice>(&mut self, loc: &Loc, dice: &mut D) -> Option<usize> {
         let t = self.get(loc);
         match t {
             BoardTile::Built {
@@ -310,7 +599,7 @@ impl Board {
                 height,
                 ..
             } => {
-                let die = roll();
+                let die = dice.roll();
                 self.set(
                     *loc,
                     BoardTile::Built {
@@ -325,36 +614,71 @@ impl Board {
         }
     }
 
-    pub fn resolve_boss_ties(&mut self) -> Option<Vec<Log>> {
-        let mut boss_tie = false;
-        let mut logs: Vec<Log> = vec![];
+    fn boss_tied_casinos(&self) -> Vec<BoardCasino> {
+        self.casinos()
+            .into_iter()
+            .filter(|bc| bc.boss_players().len() > 1)
+            .collect()
+    }
+
+    /// Rerolls the boss dice of every casino with a boss tie until there are no ties left, giving
+    /// up after `MAX_BOSS_TIE_PASSES` passes. If the dice run out it stops with a
+    /// `WaitingForDice` event, and can be run again once more rolls are available. Returns the
+    /// events describing each step, which is empty if there were no ties.
+    pub fn resolve_boss_ties<D: Dice>(&mut self, dice: &mut D) -> Vec<GameEvent> {
+        let mut events: Vec<GameEvent> = vec![];
 
-        for bc in self.casinos() {
-            let boss_tiles = bc.boss_tiles();
-            let bosses: HashSet<usize> = HashSet::from_iter(
-                boss_tiles
-                    .iter()
-                    .filter_map(|bt| bt.owner.map(|to| to.player)),
-            );
-            if bosses.len() <= 1 {
-                // There is no boss tie.
-                continue;
+        for pass in 1..MAX_BOSS_TIE_PASSES + 1 {
+            let tied = self.boss_tied_casinos();
+            if tied.is_empty() {
+                break;
             }
-            boss_tie = true;
-            for bt in &boss_tiles {
-                self.reroll_at(&bt.loc);
+            if pass > 1 {
+                events.push(GameEvent::BossTiePass { pass });
+            }
+            for bc in tied {
+                let needed = bc.boss_tiles().len();
+                if !dice.has(needed) {
+                    events.push(GameEvent::WaitingForDice { needed });
+                    return events;
+                }
+                events.push(GameEvent::BossTie {
+                    loc: bc.loc(),
+                    casino: bc.casino,
+                    players: bc.boss_players(),
+                });
+                for bt in bc.boss_tiles() {
+                    if let (Some(TileOwner { player, die: from }), Some(to)) =
+                        (bt.owner, self.reroll_at(&bt.loc, dice))
+                    {
+                        events.push(GameEvent::DieRerolled {
+                            player,
+                            loc: bt.loc,
+                            from,
+                            to,
+                        });
+                    }
+                }
+                let bosses = self.casino_at(&bc.loc())
+                    .map(|bc| bc.boss_players())
+                    .unwrap_or_default();
+                if bosses.len() == 1 {
+                    events.push(GameEvent::NewBoss {
+                        player: bosses[0],
+                        loc: bc.loc(),
+                        casino: bc.casino,
+                    });
+                }
             }
         }
 
-        if boss_tie {
-            // Do another pass, we may have created a new boss tie.
-            if let Some(new_logs) = self.resolve_boss_ties() {
-                logs.extend(new_logs);
-            }
-            Some(logs)
-        } else {
-            None
+        if !self.boss_tied_casinos().is_empty() {
+            events.push(GameEvent::BossTieUnresolved {
+                passes: MAX_BOSS_TIE_PASSES,
+            });
         }
+
+        events
     }
 }
 
@@ -372,6 +696,45 @@ pub struct BoardCasino {
 }
 
 impl BoardCasino {
+    /// The player who is boss of the casino, if any tiles are owned.
+    pub fn boss(&self) -> Option<usize> {
+        self.boss_tiles()
+            .first()
+            .and_then(|t| t.owner.map(|o| o.player))
+    }
+
+    /// The distinct players who own the highest dice in the casino, more than one is a boss tie.
+    pub fn boss_players(&self) -> Vec<usize> {
+        let mut players: Vec<usize> = self.boss_tiles()
+            .iter()
+            .filter_map(|t| t.owner.map(|o| o.player))
+            .collect();
+        players.sort();
+        players.dedup();
+        players
+    }
+
+    /// Whether the player has a die in the casino.
+    pub fn has_player(&self, p: usize) -> bool {
+        self.tiles
+            .iter()
+            .any(|t| t.owner.map(|o| o.player) == Some(p))
+    }
+
+    /// Whether any tile of the casino is on the Strip.
+    pub fn on_strip(&self) -> bool {
+        self.tiles.iter().any(|t| t.loc.on_strip())
+    }
+
+    /// The lowest location in the casino, used to refer to it in logs.
+    pub fn loc(&self) -> Loc {
+        self.tiles
+            .iter()
+            .map(|t| t.loc)
+            .min()
+            .expect("casino should have tiles")
+    }
+
     pub fn boss_tiles(&self) -> Vec<CasinoTile> {
         let mut highest: usize = 0;
         let mut bosses: Vec<CasinoTile> = vec![];
@@ -392,13 +755,17 @@ impl BoardCasino {
 
 impl Default for Board {
     fn default() -> Self {
-        Board(HashMap::new())
+        Board {
+            tiles: vec![BoardTile::Unowned; lot_count()],
+            components: vec![None; lot_count()],
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tile::TILES;
 
     fn assert_neighbours<I: Into<Loc>>(l: I, n: Vec<I>) {
         let mut expected = n.into_iter().map(|n| n.into()).collect::<Vec<Loc>>();
@@ -421,6 +788,76 @@ mod tests {
         assert_neighbours((C, 8), vec![(C, 5), (C, 7), (C, 9), (C, 11)]);
     }
 
+    #[test]
+    fn loc_try_from_works() {
+        assert_eq!(Ok(Loc::from((Block::C, 12))), Loc::try_from("C12"));
+        assert!(Loc::try_from("A0").is_err());
+        assert!(Loc::try_from("G1").is_err());
+        assert!(Loc::try_from("A").is_err());
+    }
+
+    #[test]
+    fn loc_index_works() {
+        for i in 0..lot_count() {
+            assert_eq!(Some(i), Loc::from_index(i).index());
+        }
+        for loc in TILES.keys() {
+            assert!(loc.index().is_some(), "expected {} to have an index", loc);
+        }
+        assert_eq!(lot_count(), TILES.len());
+        assert_eq!(None, Loc::from((Block::A, 0)).index());
+        assert_eq!(None, Loc::from((Block::A, 7)).index());
+    }
+
+    #[test]
+    fn board_json_skips_unowned() {
+        use serde_json;
+
+        let mut b = Board::default();
+        b.set((Block::C, 2).into(), BoardTile::Owned { player: 1 });
+        b.set((Block::A, 3).into(), BoardTile::Owned { player: 0 });
+        let json = serde_json::to_string(&b).expect("could not serialise board");
+        assert_eq!(
+            r#"{"A3":{"Owned":{"player":0}},"C2":{"Owned":{"player":1}}}"#,
+            json
+        );
+        let loaded: Board = serde_json::from_str(&json).expect("could not deserialise board");
+        let owned = loaded
+            .tiles()
+            .filter(|&(_, bt)| match bt {
+                BoardTile::Owned { .. } => true,
+                _ => false,
+            })
+            .count();
+        assert_eq!(2, owned);
+    }
+
+    #[test]
+    fn board_json_rejects_off_board_locs() {
+        use serde_json;
+
+        assert!(serde_json::from_str::<Board>(r#"{"A13":{"Owned":{"player":0}}}"#).is_err());
+        assert!(serde_json::from_str::<Board>(r#"{"A0":{"Owned":{"player":0}}}"#).is_err());
+    }
+
+    #[test]
+    fn strip_works() {
+        use self::Block::*;
+
+        for (loc, tile) in TILES.iter() {
+            assert_eq!(tile.strip, loc.on_strip(), "strip mismatch at {}", loc);
+        }
+        assert!(Loc::from((A, 3)).on_strip());
+        assert!(!Loc::from((A, 2)).on_strip());
+        assert_eq!(vec![Loc::from((B, 4))], Loc::from((A, 6)).across_strip());
+        assert_eq!(vec![Loc::from((D, 7))], Loc::from((C, 9)).across_strip());
+        assert_eq!(vec![Loc::from((F, 1))], Loc::from((C, 12)).across_strip());
+        assert_eq!(vec![Loc::from((C, 12))], Loc::from((F, 1)).across_strip());
+        assert_eq!(vec![Loc::from((E, 3))], Loc::from((F, 4)).across_strip());
+        assert_eq!(vec![Loc::from((E, 6))], Loc::from((F, 7)).across_strip());
+        assert!(Loc::from((A, 1)).across_strip().is_empty());
+    }
+
     #[test]
     fn test_board_casino_at_works() {
         let mut b = Board::default();
@@ -533,6 +970,143 @@ mod tests {
         );
     }
 
+    #[test]
+    fn casino_index_merges_and_splits() {
+        use serde_json;
+
+        let built = |casino| BoardTile::Built {
+            casino,
+            owner: None,
+            height: 1,
+        };
+        let mut b = Board::default();
+        b.set((Block::A, 1).into(), built(Casino::Albion));
+        b.set((Block::A, 3).into(), built(Casino::Albion));
+        assert_eq!(2, b.casinos().len());
+
+        // Building between them joins them into one casino.
+        b.set((Block::A, 2).into(), built(Casino::Albion));
+        assert_eq!(1, b.casinos().len());
+        assert_eq!(
+            3,
+            b.casino_at(&(Block::A, 3).into())
+                .expect("expected casino")
+                .tiles
+                .len()
+        );
+
+        // Recolouring the middle splits it into three.
+        b.set((Block::A, 2).into(), built(Casino::Vega));
+        assert_eq!(3, b.casinos().len());
+        assert_eq!(
+            1,
+            b.casino_at(&(Block::A, 1).into())
+                .expect("expected casino")
+                .tiles
+                .len()
+        );
+
+        // The index is rebuilt when loading.
+        let json = serde_json::to_string(&b).expect("could not serialise board");
+        let loaded: Board = serde_json::from_str(&json).expect("could not deserialise board");
+        assert!(loaded.components_match_flood());
+        assert_eq!(3, loaded.casinos().len());
+    }
+
+    #[test]
+    fn resolve_boss_ties_works() {
+        use dice::ScriptedDice;
+
+        let mut b = Board::default();
+        b.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Albion,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: 1,
+            },
+        );
+        b.set(
+            (Block::A, 2).into(),
+            BoardTile::Built {
+                casino: Casino::Albion,
+                owner: Some(TileOwner { die: 3, player: 1 }),
+                height: 1,
+            },
+        );
+        // The first reroll ties again, the second pass gives player 1 the casino.
+        let mut dice = ScriptedDice::new(vec![4, 4, 2, 5]);
+        assert!(!b.resolve_boss_ties(&mut dice).is_empty());
+        assert_eq!(
+            Some(1),
+            b.casino_at(&(Block::A, 1).into())
+                .expect("expected casino")
+                .boss()
+        );
+        assert_eq!(0, dice.remaining(), "expected all dice to be used");
+    }
+
+    #[test]
+    fn resolve_boss_ties_waits_for_dice() {
+        use dice::ScriptedDice;
+
+        let mut b = Board::default();
+        for lot in 1..3 {
+            b.set(
+                (Block::A, lot).into(),
+                BoardTile::Built {
+                    casino: Casino::Albion,
+                    owner: Some(TileOwner {
+                        die: 3,
+                        player: lot,
+                    }),
+                    height: 1,
+                },
+            );
+        }
+        let mut dice = ScriptedDice::new(vec![4]);
+        assert_eq!(
+            vec![GameEvent::WaitingForDice { needed: 2 }],
+            b.resolve_boss_ties(&mut dice)
+        );
+        assert_eq!(1, dice.remaining(), "expected no dice to be used");
+    }
+
+    #[test]
+    fn resolve_boss_ties_gives_up() {
+        use dice::ScriptedDice;
+
+        let mut b = Board::default();
+        for lot in 1..3 {
+            b.set(
+                (Block::A, lot).into(),
+                BoardTile::Built {
+                    casino: Casino::Albion,
+                    owner: Some(TileOwner {
+                        die: 3,
+                        player: lot,
+                    }),
+                    height: 1,
+                },
+            );
+        }
+        let mut dice = ScriptedDice::new(vec![4; MAX_BOSS_TIE_PASSES * 2]);
+        let events = b.resolve_boss_ties(&mut dice);
+        assert_eq!(
+            Some(&GameEvent::BossTieUnresolved {
+                passes: MAX_BOSS_TIE_PASSES,
+            }),
+            events.last()
+        );
+        assert_eq!(
+            2,
+            b.casino_at(&(Block::A, 1).into())
+                .expect("expected casino")
+                .boss_players()
+                .len()
+        );
+    }
+
     #[test]
     fn test_board_casinos_works() {
         let mut b = Board::default();
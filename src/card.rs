@@ -1,4 +1,4 @@
-use rand::{self, Rng};
+use rand::Rng;
 
 use brdgme_markup::Node as N;
 
@@ -9,19 +9,17 @@ use tile::{Payout, TILES};
 use STARTING_CARDS;
 use casino::Casino;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Card {
     Loc { loc: Loc },
     GameEnd,
 }
 
-pub fn shuffled_deck(players: usize) -> Vec<Card> {
-    let mut rng = rand::thread_rng();
-    let mut cards: Vec<Card> = TILES
-        .keys()
-        .cloned()
-        .map(|t| Card::Loc { loc: t })
-        .collect();
+pub fn shuffled_deck<R: Rng>(players: usize, rng: &mut R) -> Vec<Card> {
+    let mut locs: Vec<Loc> = TILES.keys().cloned().collect();
+    // Sort before shuffling so the deck only depends on the RNG and not on map ordering.
+    locs.sort();
+    let mut cards: Vec<Card> = locs.into_iter().map(|t| Card::Loc { loc: t }).collect();
     rng.shuffle(&mut cards);
     // Insert the game end card in the last quarter of the deck, taking into account the cards which
     // will be drawn by the players as adding the end card happens after players draw.
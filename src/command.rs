@@ -1,170 +1,547 @@
 use brdgme_game::command::parser::*;
 
+use std::fmt;
+
 use Game;
 use casino::{Casino, CASINOS};
+use dice::Dice;
 use board::Loc;
 use tile::TILES;
+use {CASINO_MAX_HEIGHT, RAISE_COST_PER_TILE, REMODEL_COST_PER_TILE, REORG_COST_PER_DIE};
+use {DIE_MAX, DIE_MIN, GAMBLE_DICE};
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     Build { loc: Loc, casino: Casino },
     Sprawl { from: Loc, to: Loc },
     Remodel { loc: Loc, casino: Casino },
     Reorg { loc: Loc },
-    Gamble { player: usize, amount: usize },
+    Gamble { loc: Loc, amount: usize },
     Raise { loc: Loc },
+    Roll { value: usize },
     Done,
 }
 
+impl Command {
+    /// The name of the command as it is typed.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Command::Build { .. } => "build",
+            Command::Sprawl { .. } => "sprawl",
+            Command::Remodel { .. } => "remodel",
+            Command::Reorg { .. } => "reorg",
+            Command::Gamble { .. } => "gamble",
+            Command::Raise { .. } => "raise",
+            Command::Roll { .. } => "roll",
+            Command::Done => "done",
+        }
+    }
+}
+
+// Commands are displayed as the input which would run them.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match *self {
+            Command::Build { loc, casino } | Command::Remodel { loc, casino } => {
+                write!(f, " {} {}", loc, casino)
+            }
+            Command::Sprawl { from, to } => write!(f, " {} {}", from, to),
+            Command::Gamble { loc, amount } => write!(f, " {} {}", loc, amount),
+            Command::Reorg { loc } | Command::Raise { loc } => write!(f, " {}", loc),
+            Command::Roll { value } => write!(f, " {}", value),
+            Command::Done => Ok(()),
+        }
+    }
+}
+
 impl Game {
     pub fn command_parser(&self, player: usize) -> Box<Parser<Command>> {
+        let legal = self.legal_commands(player);
+        let offers = |name: &str| legal.iter().any(|c| c.name() == name);
         let mut parsers: Vec<Box<Parser<Command>>> = vec![];
-        if self.can_build(player) {
-            parsers.push(Box::new(self.build_parser(player)));
+        if offers("build") {
+            parsers.push(Box::new(build_parser(&legal)));
         }
-        if self.can_done(player) {
+        if offers("sprawl") {
+            parsers.push(Box::new(sprawl_parser(&legal)));
+        }
+        if offers("remodel") {
+            parsers.push(Box::new(remodel_parser(&legal)));
+        }
+        if offers("reorg") {
+            parsers.push(Box::new(reorg_parser(&legal)));
+        }
+        if offers("gamble") {
+            parsers.push(Box::new(gamble_parser(&legal)));
+        }
+        if offers("raise") {
+            parsers.push(Box::new(raise_parser(&legal)));
+        }
+        if offers("roll") {
+            parsers.push(Box::new(roll_parser(&legal)));
+        }
+        if offers("done") {
             parsers.push(Box::new(done_parser()));
         }
         Box::new(OneOf::new(parsers))
     }
 
-    pub fn build_parser(&self, player: usize) -> impl Parser<Command> {
-        Map::new(
-            Chain3::new(
-                Doc::name_desc("build", "build a casino at a location", Token::new("build")),
-                AfterSpace::new(Doc::name_desc(
-                    "loc",
-                    "the location to build at",
-                    loc_parser(self.board.player_locs(player)),
-                )),
-                AfterSpace::new(Doc::name_desc(
-                    "casino",
-                    "the casino to build",
-                    casino_parser(),
-                )),
-            ),
-            |(_, loc, casino)| Command::Build { loc, casino },
-        )
+    /// Every command the player can successfully run in the current state, with each location of
+    /// a casino listed as a separate command as any of them can be used to refer to it. Commands
+    /// which roll dice are only listed when enough dice are available for them.
+    pub fn legal_commands(&self, player: usize) -> Vec<Command> {
+        let mut commands: Vec<Command> = vec![];
+        if self.can_roll(player) {
+            commands.extend((DIE_MIN..DIE_MAX + 1).map(|value| Command::Roll { value }));
+        }
+        if !self.can_act(player) {
+            return commands;
+        }
+        let cash = self.players[player].cash;
+        let has_dice = self.board.remaining_dice(player) > 0;
+        let available = self.available_casinos();
+        let boss_casinos = self.board.boss_casinos(player);
+
+        if self.can_build(player) && has_dice {
+            let mut locs = self.board.player_locs(player);
+            locs.sort();
+            for loc in locs {
+                if TILES[&loc].build_cost > cash {
+                    continue;
+                }
+                for casino in &available {
+                    commands.push(Command::Build {
+                        loc,
+                        casino: *casino,
+                    });
+                }
+            }
+        }
+
+        if self.can_sprawl(player) && has_dice {
+            for bc in &boss_casinos {
+                if self.board.remaining_casino_tiles(&bc.casino) == 0 {
+                    continue;
+                }
+                let mut to_locs = self.board.sprawl_locs(bc);
+                to_locs.sort();
+                for t in &bc.tiles {
+                    for to in &to_locs {
+                        if TILES[to].build_cost <= cash {
+                            commands.push(Command::Sprawl {
+                                from: t.loc,
+                                to: *to,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.can_remodel(player) {
+            for bc in &boss_casinos {
+                if bc.tiles.len() * REMODEL_COST_PER_TILE > cash {
+                    continue;
+                }
+                for t in &bc.tiles {
+                    for casino in CASINOS {
+                        if *casino != bc.casino
+                            && self.board.remaining_casino_tiles(casino) >= bc.tiles.len()
+                        {
+                            commands.push(Command::Remodel {
+                                loc: t.loc,
+                                casino: *casino,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.can_reorg(player) {
+            for bc in self.board.casinos() {
+                let dice = bc.tiles.iter().filter(|t| t.owner.is_some()).count();
+                if bc.has_player(player) && dice * REORG_COST_PER_DIE <= cash
+                    && self.dice.has(dice)
+                {
+                    commands.extend(bc.tiles.iter().map(|t| Command::Reorg { loc: t.loc }));
+                }
+            }
+        }
+
+        if self.can_gamble(player) && self.dice.has(GAMBLE_DICE * 2) {
+            for bc in self.board.casinos() {
+                match bc.boss() {
+                    Some(boss) if boss != player => {}
+                    _ => continue,
+                }
+                let max = self.gamble_max(player, &bc);
+                for t in &bc.tiles {
+                    for amount in 1..max + 1 {
+                        commands.push(Command::Gamble {
+                            loc: t.loc,
+                            amount,
+                        });
+                    }
+                }
+            }
+        }
+
+        if self.can_raise(player) {
+            for bc in &boss_casinos {
+                if bc.height < CASINO_MAX_HEIGHT && bc.tiles.len() * RAISE_COST_PER_TILE <= cash {
+                    commands.extend(bc.tiles.iter().map(|t| Command::Raise { loc: t.loc }));
+                }
+            }
+        }
+
+        if self.can_done(player) {
+            commands.push(Command::Done);
+        }
+        commands
     }
 
-    pub fn sprawl_parser(&self) -> impl Parser<Command> {
-        Map::new(
-            Chain3::new(
-                Doc::name_desc(
-                    "sprawl",
-                    "sprawl a casino you are the boss of to an adjacent location",
-                    Token::new("sprawl"),
-                ),
-                AfterSpace::new(Doc::name_desc(
-                    "from",
-                    "the casino to sprawl from",
-                    loc_parser(TILES.keys().cloned().collect()),
-                )),
-                AfterSpace::new(Doc::name_desc(
-                    "to",
-                    "the empty location to sprawl to",
-                    loc_parser(TILES.keys().cloned().collect()),
-                )),
-            ),
-            |(_, from, to)| Command::Sprawl { from, to },
-        )
+    /// Casinos which still have tiles left in the supply.
+    fn available_casinos(&self) -> Vec<Casino> {
+        CASINOS
+            .iter()
+            .filter(|c| self.board.remaining_casino_tiles(c) > 0)
+            .cloned()
+            .collect()
     }
+}
 
-    pub fn remodel_action(&self) -> impl Parser<Command> {
-        Map::new(
-            Chain3::new(
-                Doc::name_desc(
-                    "remodel",
-                    "remodel a casino you are the boss of to a different color",
-                    Token::new("remodel"),
-                ),
-                AfterSpace::new(Doc::name_desc(
-                    "loc",
-                    "a location of the casino to remodel",
-                    loc_parser(TILES.keys().cloned().collect()),
-                )),
-                AfterSpace::new(Doc::name_desc(
-                    "casino",
-                    "the color to remodel to",
-                    casino_parser(),
-                )),
+fn build_parser(legal: &[Command]) -> impl Parser<Command> {
+    let pairs: Vec<(Loc, Casino)> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Build { loc, casino } => Some((loc, casino)),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc("build", "build a casino at a location", Token::new("build")),
+            AfterSpace::new(loc_arg_parser(
+                pairs,
+                ("loc", "the location to build at"),
+                ("casino", "the casino to build"),
+                casino_parser,
+            )),
+        ),
+        |(_, (loc, casino))| Command::Build { loc, casino },
+    )
+}
+
+fn sprawl_parser(legal: &[Command]) -> impl Parser<Command> {
+    let pairs: Vec<(Loc, Loc)> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Sprawl { from, to } => Some((from, to)),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "sprawl",
+                "sprawl a casino you are the boss of to an adjacent location",
+                Token::new("sprawl"),
             ),
-            |(_, loc, casino)| Command::Remodel { loc, casino },
-        )
-    }
+            AfterSpace::new(loc_arg_parser(
+                pairs,
+                ("from", "the casino to sprawl from"),
+                ("to", "the empty location to sprawl to"),
+                loc_parser,
+            )),
+        ),
+        |(_, (from, to))| Command::Sprawl { from, to },
+    )
+}
 
-    pub fn reorg_parser(&self) -> impl Parser<Command> {
-        Map::new(
-            Chain2::new(
-                Doc::name_desc(
-                    "reorg",
-                    "reroll all the dice in a casino that you have a dice in",
-                    Token::new("reorg"),
-                ),
-                AfterSpace::new(Doc::name_desc(
-                    "loc",
-                    "a location of the casino to reorg",
-                    loc_parser(TILES.keys().cloned().collect()),
-                )),
+fn remodel_parser(legal: &[Command]) -> impl Parser<Command> {
+    let pairs: Vec<(Loc, Casino)> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Remodel { loc, casino } => Some((loc, casino)),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "remodel",
+                "remodel a casino you are the boss of to a different color",
+                Token::new("remodel"),
             ),
-            |(_, loc)| Command::Reorg { loc },
-        )
-    }
+            AfterSpace::new(loc_arg_parser(
+                pairs,
+                ("loc", "a location of the casino to remodel"),
+                ("casino", "the color to remodel to"),
+                casino_parser,
+            )),
+        ),
+        |(_, (loc, casino))| Command::Remodel { loc, casino },
+    )
+}
 
-    pub fn gamble_parser(&self) -> impl Parser<Command> {
-        Map::new(
-            Chain3::new(
-                Doc::name_desc(
-                    "gamble",
-                    "gamble at an opponent's casino",
-                    Token::new("gamble"),
-                ),
-                AfterSpace::new(Doc::name_desc(
-                    "player",
-                    "the player whose casino you want to gamble at",
-                    Player {},
-                )),
-                AfterSpace::new(Doc::name_desc(
-                    "amount",
-                    "the amount to gamble, maximum $5 per tile",
-                    money_parser(),
-                )),
+fn reorg_parser(legal: &[Command]) -> impl Parser<Command> {
+    let locs: Vec<Loc> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Reorg { loc } => Some(loc),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "reorg",
+                "reroll all the dice in a casino that you have a dice in",
+                Token::new("reorg"),
             ),
-            |(_, player, amount)| Command::Gamble { player, amount },
-        )
-    }
+            AfterSpace::new(Doc::name_desc(
+                "loc",
+                "a location of the casino to reorg",
+                loc_parser(locs),
+            )),
+        ),
+        |(_, loc)| Command::Reorg { loc },
+    )
+}
 
-    pub fn raise_parser(&self) -> impl Parser<Command> {
-        Map::new(
-            Chain2::new(
-                Doc::name_desc(
-                    "raise",
-                    "raise a casino you are the boss of by a level",
-                    Token::new("raise"),
-                ),
-                AfterSpace::new(Doc::name_desc(
-                    "loc",
-                    "a location of the casino to raise",
-                    loc_parser(TILES.keys().cloned().collect()),
-                )),
+fn gamble_parser(legal: &[Command]) -> impl Parser<Command> {
+    let pairs: Vec<(Loc, usize)> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Gamble { loc, amount } => Some((loc, amount)),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "gamble",
+                "gamble at an opponent's casino",
+                Token::new("gamble"),
             ),
-            |(_, loc)| Command::Raise { loc },
-        )
-    }
+            AfterSpace::new(loc_arg_parser(
+                pairs,
+                ("loc", "a location of the casino you want to gamble at"),
+                ("amount", "the amount to gamble, maximum $5 per tile"),
+                amount_parser,
+            )),
+        ),
+        |(_, (loc, amount))| Command::Gamble { loc, amount },
+    )
+}
+
+fn raise_parser(legal: &[Command]) -> impl Parser<Command> {
+    let locs: Vec<Loc> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Raise { loc } => Some(loc),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "raise",
+                "raise a casino you are the boss of by a level",
+                Token::new("raise"),
+            ),
+            AfterSpace::new(Doc::name_desc(
+                "loc",
+                "a location of the casino to raise",
+                loc_parser(locs),
+            )),
+        ),
+        |(_, loc)| Command::Raise { loc },
+    )
+}
+
+fn roll_parser(legal: &[Command]) -> impl Parser<Command> {
+    let values: Vec<usize> = legal
+        .iter()
+        .filter_map(|c| match *c {
+            Command::Roll { value } => Some(value),
+            _ => None,
+        })
+        .collect();
+    Map::new(
+        Chain2::new(
+            Doc::name_desc(
+                "roll",
+                "enter the value of a physical die which was rolled",
+                Token::new("roll"),
+            ),
+            AfterSpace::new(Doc::name_desc(
+                "value",
+                "the value rolled",
+                Enum::exact(values),
+            )),
+        ),
+        |(_, value)| Command::Roll { value },
+    )
 }
 
 fn loc_parser(mut locs: Vec<Loc>) -> impl Parser<Loc> {
     locs.sort();
+    locs.dedup();
     Enum::exact(locs)
 }
 
-fn casino_parser() -> impl Parser<Casino> {
-    Enum::partial(CASINOS.to_owned())
+fn casino_parser(casinos: Vec<Casino>) -> impl Parser<Casino> {
+    Enum::partial(casinos)
 }
 
-fn money_parser() -> impl Parser<usize> {
-    Map::new(Int::positive(), |i| i as usize)
+/// Parses an amount of money. The legal amounts always run from $1 up to a maximum, so only the
+/// maximum is needed.
+fn amount_parser(amounts: Vec<usize>) -> impl Parser<usize> {
+    let max = amounts.into_iter().max().unwrap_or(0);
+    Map::new(Int::bounded(1, max as i32), |i| i as usize)
+}
+
+/// Parses a location followed by an argument, accepting only the given pairs. Each location gets
+/// its own branch, so the argument after it only accepts the values which are legal with it.
+fn loc_arg_parser<T, P, F>(
+    pairs: Vec<(Loc, T)>,
+    loc_doc: (&str, &str),
+    arg_doc: (&str, &str),
+    arg_parser: F,
+) -> impl Parser<(Loc, T)>
+where
+    T: Clone + 'static,
+    P: Parser<T> + 'static,
+    F: Fn(Vec<T>) -> P,
+{
+    let mut locs: Vec<Loc> = pairs.iter().map(|&(loc, _)| loc).collect();
+    locs.sort();
+    locs.dedup();
+    let parsers: Vec<Box<Parser<(Loc, T)>>> = locs
+        .into_iter()
+        .map(|loc| {
+            let args: Vec<T> = pairs
+                .iter()
+                .filter(|&&(l, _)| l == loc)
+                .map(|&(_, ref arg)| arg.clone())
+                .collect();
+            Box::new(Chain2::new(
+                Doc::name_desc(loc_doc.0, loc_doc.1, Enum::exact(vec![loc])),
+                AfterSpace::new(Doc::name_desc(arg_doc.0, arg_doc.1, arg_parser(args))),
+            )) as Box<Parser<(Loc, T)>>
+        })
+        .collect();
+    OneOf::new(parsers)
 }
 
 fn done_parser() -> impl Parser<Command> {
     Map::new(Token::new("done"), |_| Command::Done)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::Rng;
+
+    use brdgme_game::Gamer;
+
+    use dice::{DiceSource, PhysicalDice};
+    use rng::GameRng;
+    use validate::Violation;
+    use {CASINO_TILES, GAMBLE_MAX_PER_TILE};
+
+    /// Checks that the parser accepts exactly the commands in `legal_commands`, trying every
+    /// location, casino and amount, and that every legal command runs.
+    fn check_legal_commands(game: &Game, names: &[String]) {
+        for p in 0..game.players.len() {
+            if p != game.current_player {
+                assert!(game.legal_commands(p).is_empty());
+            }
+        }
+        let player = game.current_player;
+        let legal = game.legal_commands(player);
+        let parser = game.command_parser(player);
+        let mut locs: Vec<Loc> = TILES.keys().cloned().collect();
+        locs.sort();
+        let mut candidates: Vec<Command> = vec![Command::Done];
+        for value in 0..DIE_MAX + 2 {
+            candidates.push(Command::Roll { value });
+        }
+        for &loc in &locs {
+            for &casino in CASINOS {
+                candidates.push(Command::Build { loc, casino });
+                candidates.push(Command::Remodel { loc, casino });
+            }
+            candidates.push(Command::Reorg { loc });
+            candidates.push(Command::Raise { loc });
+            for &to in &locs {
+                candidates.push(Command::Sprawl { from: loc, to });
+            }
+            for amount in 1..GAMBLE_MAX_PER_TILE * CASINO_TILES + 2 {
+                candidates.push(Command::Gamble { loc, amount });
+            }
+        }
+        for candidate in candidates {
+            let input = candidate.to_string();
+            let is_legal = legal.contains(&candidate);
+            match parser.parse(&input, names) {
+                Ok(output) => {
+                    assert!(is_legal, "illegal command {} parsed", input);
+                    assert_eq!(candidate, output.value, "{} parsed differently", input);
+                    assert_eq!("", output.remaining, "{} wasn't fully parsed", input);
+                }
+                Err(_) => assert!(!is_legal, "legal command {} didn't parse", input),
+            }
+        }
+        for command in &legal {
+            if let Err(e) = game.clone().command(player, &command.to_string(), names) {
+                panic!("legal command {} failed: {}", command, e);
+            }
+        }
+    }
+
+    /// Plays random legal commands, regularly checking the parser against `legal_commands`.
+    fn play_checking_parser(mut game: Game, seed: u64) {
+        let names: Vec<String> = (0..game.players.len())
+            .map(|p| format!("player{}", p + 1))
+            .collect();
+        let mut rng = GameRng::from_seed(seed);
+        let mut actions = 0;
+        for step in 0..60 {
+            if game.finished {
+                break;
+            }
+            if step % 6 == 0 {
+                check_legal_commands(&game, &names);
+            }
+            let p = game.current_player;
+            let legal = game.legal_commands(p);
+            let command = if actions >= 3 && legal.contains(&Command::Done) {
+                Command::Done
+            } else {
+                legal[rng.gen_range(0, legal.len())].clone()
+            };
+            actions = if command == Command::Done { 0 } else { actions + 1 };
+            game.command(p, &command.to_string(), &names)
+                .unwrap_or_else(|e| panic!("legal command {} failed: {}", command, e));
+            assert_eq!(Vec::<Violation>::new(), game.validate());
+        }
+    }
+
+    #[test]
+    fn legal_commands_match_parser() {
+        let (game, _) = Game::with_seed(3, 11).expect("could not create game");
+        play_checking_parser(game, 11);
+    }
+
+    #[test]
+    fn legal_commands_match_parser_with_physical_dice() {
+        let (mut game, _) = Game::with_seed(3, 12).expect("could not create game");
+        game.dice = DiceSource::Physical(PhysicalDice::default());
+        play_checking_parser(game, 12);
+    }
+}
@@ -0,0 +1,216 @@
+use rand::Rng;
+
+use brdgme_game::errors::GameError;
+
+use std::collections::VecDeque;
+
+use rng::GameRng;
+use {DIE_MAX, DIE_MIN};
+
+/// A source of die rolls, so the game can be played with fair dice, scripted dice for tests, or
+/// physical dice entered by a moderator. Sources with a limited supply report how many rolls are
+/// available, and anything rolling checks there are enough before it changes the game, so
+/// rolling itself can't fail and commands never need to be rolled back part way through.
+pub trait Dice {
+    /// Rolls a die, panicking if `available` says there are no rolls left.
+    fn roll(&mut self) -> usize;
+
+    /// The number of rolls available, or `None` if there is no limit.
+    fn available(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether there are at least `count` rolls available.
+    fn has(&self, count: usize) -> bool {
+        self.available().map(|a| a >= count).unwrap_or(true)
+    }
+}
+
+fn check_die(value: usize) -> Result<usize, GameError> {
+    if value < DIE_MIN || value > DIE_MAX {
+        return Err(GameError::InvalidInput {
+            message: format!(
+                "die value must be between {} and {}, got {}",
+                DIE_MIN, DIE_MAX, value
+            ),
+        });
+    }
+    Ok(value)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FairDice {
+    rng: GameRng,
+}
+
+impl FairDice {
+    pub fn from_seed(seed: u64) -> Self {
+        FairDice {
+            rng: GameRng::from_seed(seed),
+        }
+    }
+}
+
+impl Dice for FairDice {
+    fn roll(&mut self) -> usize {
+        self.rng.gen_range(DIE_MIN, DIE_MAX + 1)
+    }
+}
+
+/// Rolls a fixed sequence of values, for setting up exact situations in tests and replays.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ScriptedDice {
+    values: Vec<usize>,
+    pos: usize,
+}
+
+impl ScriptedDice {
+    pub fn new(values: Vec<usize>) -> Self {
+        for &v in &values {
+            assert!(
+                v >= DIE_MIN && v <= DIE_MAX,
+                "die value must be between {} and {}, got {}",
+                DIE_MIN,
+                DIE_MAX,
+                v
+            );
+        }
+        ScriptedDice { values, pos: 0 }
+    }
+
+    /// Every scripted value, including those already rolled.
+    pub fn values(&self) -> &[usize] {
+        &self.values
+    }
+
+    /// The number of values which haven't been rolled yet.
+    pub fn remaining(&self) -> usize {
+        self.values.len() - self.pos
+    }
+}
+
+impl Dice for ScriptedDice {
+    fn roll(&mut self) -> usize {
+        let value = *self.values
+            .get(self.pos)
+            .expect("scripted dice have run out of values");
+        self.pos += 1;
+        value
+    }
+
+    fn available(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+}
+
+/// Dice rolled at the table, with the values entered by a moderator using the roll command before
+/// they are used.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PhysicalDice {
+    pending: VecDeque<usize>,
+}
+
+impl PhysicalDice {
+    pub fn enter(&mut self, value: usize) -> Result<(), GameError> {
+        self.pending.push_back(check_die(value)?);
+        Ok(())
+    }
+}
+
+impl Dice for PhysicalDice {
+    fn roll(&mut self) -> usize {
+        self.pending
+            .pop_front()
+            .expect("physical dice should have been entered")
+    }
+
+    fn available(&self) -> Option<usize> {
+        Some(self.pending.len())
+    }
+}
+
+/// The dice owned by `Game`, wrapped in an enum so the chosen source is serialised with the game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum DiceSource {
+    Fair(FairDice),
+    Scripted(ScriptedDice),
+    Physical(PhysicalDice),
+}
+
+impl Default for DiceSource {
+    fn default() -> Self {
+        DiceSource::Fair(FairDice::default())
+    }
+}
+
+impl Dice for DiceSource {
+    fn roll(&mut self) -> usize {
+        match *self {
+            DiceSource::Fair(ref mut d) => d.roll(),
+            DiceSource::Scripted(ref mut d) => d.roll(),
+            DiceSource::Physical(ref mut d) => d.roll(),
+        }
+    }
+
+    fn available(&self) -> Option<usize> {
+        match *self {
+            DiceSource::Fair(ref d) => d.available(),
+            DiceSource::Scripted(ref d) => d.available(),
+            DiceSource::Physical(ref d) => d.available(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fair_dice_are_fair() {
+        let mut dice = FairDice::from_seed(7);
+        let rolls = 6000;
+        let mut counts = [0usize; DIE_MAX + 1];
+        for _ in 0..rolls {
+            counts[dice.roll()] += 1;
+        }
+        assert_eq!(0, counts[0]);
+        let expected = rolls / (DIE_MAX - DIE_MIN + 1);
+        for face in DIE_MIN..DIE_MAX + 1 {
+            assert!(
+                counts[face] > expected * 8 / 10 && counts[face] < expected * 12 / 10,
+                "face {} rolled {} times, expected about {}",
+                face,
+                counts[face],
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn scripted_dice_work() {
+        let mut dice = ScriptedDice::new(vec![6, 1]);
+        assert_eq!(6, dice.roll());
+        assert_eq!(1, dice.roll());
+        assert_eq!(0, dice.remaining());
+    }
+
+    #[test]
+    fn physical_dice_work() {
+        let mut dice = PhysicalDice::default();
+        assert!(!dice.has(1));
+        dice.enter(4).expect("expected 4 to be entered");
+        dice.enter(2).expect("expected 2 to be entered");
+        assert!(dice.enter(0).is_err());
+        assert!(dice.enter(DIE_MAX + 1).is_err());
+        assert!(dice.has(2));
+        assert_eq!(4, dice.roll());
+        assert_eq!(2, dice.roll());
+        assert_eq!(Some(0), dice.available());
+    }
+
+    #[test]
+    #[should_panic]
+    fn scripted_dice_check_values() {
+        ScriptedDice::new(vec![7]);
+    }
+}
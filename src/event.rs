@@ -0,0 +1,432 @@
+use brdgme_game::Log;
+use brdgme_markup::Node as N;
+
+use board::Loc;
+use card::{render_cards, Card};
+use casino::Casino;
+use render::render_cash;
+
+/// A change to the game state. Every mutation emits events and the logs shown to players are
+/// generated from them, so bots and stats tools can consume the events directly.
+///
+/// Cash and point movements are also emitted as `CashTransferred` and `PointsAwarded` so balances
+/// can be tracked without knowing every action. These don't produce logs of their own as the
+/// event which caused them already describes them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    CardsDealt {
+        player: usize,
+        cards: Vec<Card>,
+    },
+    StartingCash {
+        player: usize,
+        amount: usize,
+    },
+    StartingPlayer {
+        player: usize,
+    },
+    CardDrawn {
+        player: usize,
+        card: Card,
+    },
+    TileClaimed {
+        player: usize,
+        loc: Loc,
+    },
+    LotAlreadyBuilt {
+        player: usize,
+        loc: Loc,
+    },
+    OutOfOwnerTokens {
+        player: usize,
+    },
+    CasinoBuilt {
+        player: usize,
+        loc: Loc,
+        casino: Casino,
+        cost: usize,
+    },
+    CasinoSprawled {
+        player: usize,
+        from: Loc,
+        to: Loc,
+        casino: Casino,
+        cost: usize,
+    },
+    CasinoRemodeled {
+        player: usize,
+        loc: Loc,
+        from: Casino,
+        to: Casino,
+        cost: usize,
+    },
+    CasinoReorganised {
+        player: usize,
+        loc: Loc,
+        casino: Casino,
+        cost: usize,
+    },
+    CasinoRaised {
+        player: usize,
+        loc: Loc,
+        casino: Casino,
+        height: usize,
+        cost: usize,
+    },
+    DieRerolled {
+        player: usize,
+        loc: Loc,
+        from: usize,
+        to: usize,
+    },
+    BossTie {
+        loc: Loc,
+        casino: Casino,
+        players: Vec<usize>,
+    },
+    BossTiePass {
+        pass: usize,
+    },
+    BossTieUnresolved {
+        passes: usize,
+    },
+    WaitingForDice {
+        needed: usize,
+    },
+    DieEntered {
+        player: usize,
+        value: usize,
+    },
+    NewBoss {
+        player: usize,
+        loc: Loc,
+        casino: Casino,
+    },
+    Gambled {
+        player: usize,
+        boss: usize,
+        loc: Loc,
+        casino: Casino,
+        amount: usize,
+        roll: usize,
+        house_roll: usize,
+        won: bool,
+    },
+    CasinoPayout {
+        casino: Casino,
+        cards_left: usize,
+    },
+    StripPayout,
+    CasinoPaid {
+        player: usize,
+        loc: Loc,
+        casino: Casino,
+        cash: usize,
+        points: usize,
+    },
+    CashTransferred {
+        from: Option<usize>,
+        to: Option<usize>,
+        amount: usize,
+    },
+    PointsAwarded {
+        player: usize,
+        stops: usize,
+        points: usize,
+    },
+    FinalScoring,
+    GameEnded,
+}
+
+fn bold(text: String) -> N {
+    N::Bold(vec![N::text(text)])
+}
+
+impl GameEvent {
+    pub fn logs(&self) -> Vec<Log> {
+        match *self {
+            GameEvent::CardsDealt {
+                player,
+                ref cards,
+            } => vec![
+                Log::private(
+                    vec![N::text("You drew "), N::Group(render_cards(cards))],
+                    vec![player],
+                ),
+            ],
+            GameEvent::StartingCash { player, amount } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" will start with "),
+                    render_cash(amount),
+                ]),
+            ],
+            GameEvent::StartingPlayer { player } => vec![
+                Log::public(vec![N::Player(player), N::text(" will start the game")]),
+            ],
+            GameEvent::CardDrawn {
+                player,
+                card: Card::GameEnd,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" drew "),
+                    Card::GameEnd.render(),
+                ]),
+            ],
+            // Claimed lots are shown by the event which follows, others stay in the hand.
+            GameEvent::CardDrawn { player, .. } => vec![
+                Log::public(vec![N::Player(player), N::text(" drew a card")]),
+            ],
+            GameEvent::TileClaimed { player, loc } => vec![
+                Log::public(vec![N::Player(player), N::text(" claimed "), loc.render()]),
+            ],
+            GameEvent::LotAlreadyBuilt { player, loc } => vec![
+                Log::private(
+                    vec![loc.render(), N::text(" has already been built on")],
+                    vec![player],
+                ),
+            ],
+            GameEvent::OutOfOwnerTokens { player } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" has no owner tokens left to claim the lot"),
+                ]),
+            ],
+            GameEvent::CasinoBuilt {
+                player,
+                loc,
+                casino,
+                ..
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" built "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                ]),
+            ],
+            GameEvent::CasinoSprawled {
+                player,
+                from,
+                to,
+                casino,
+                ..
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" sprawled "),
+                    casino.render(),
+                    N::text(" from "),
+                    from.render(),
+                    N::text(" to "),
+                    to.render(),
+                ]),
+            ],
+            GameEvent::CasinoRemodeled {
+                player,
+                loc,
+                from,
+                to,
+                cost,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" remodeled "),
+                    from.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(" to "),
+                    to.render(),
+                    N::text(" for "),
+                    render_cash(cost),
+                ]),
+            ],
+            GameEvent::CasinoReorganised {
+                player,
+                loc,
+                casino,
+                cost,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" reorganised "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(" for "),
+                    render_cash(cost),
+                ]),
+            ],
+            GameEvent::CasinoRaised {
+                player,
+                loc,
+                casino,
+                height,
+                cost,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" raised "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(" to height "),
+                    bold(format!("{}", height)),
+                    N::text(" for "),
+                    render_cash(cost),
+                ]),
+            ],
+            GameEvent::DieRerolled {
+                player,
+                loc,
+                from,
+                to,
+            } => vec![
+                Log::public(vec![
+                    loc.render(),
+                    N::text(": "),
+                    N::Player(player),
+                    N::text(" rerolled "),
+                    bold(format!("{}", from)),
+                    N::text(" to "),
+                    bold(format!("{}", to)),
+                ]),
+            ],
+            GameEvent::BossTie {
+                loc,
+                casino,
+                ref players,
+            } => {
+                let mut content: Vec<N> = vec![
+                    N::text("Boss tie at "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(" between "),
+                ];
+                for (i, p) in players.iter().enumerate() {
+                    if i > 0 {
+                        content.push(N::text(if i == players.len() - 1 {
+                            " and "
+                        } else {
+                            ", "
+                        }));
+                    }
+                    content.push(N::Player(*p));
+                }
+                vec![Log::public(content)]
+            }
+            GameEvent::BossTiePass { pass } => vec![
+                Log::public(vec![
+                    N::text("Boss ties remain, rerolling again (pass "),
+                    bold(format!("{}", pass)),
+                    N::text(")"),
+                ]),
+            ],
+            GameEvent::BossTieUnresolved { passes } => vec![
+                Log::public(vec![
+                    N::text(format!(
+                        "Boss ties still remain after {} passes, they will be rerolled next time",
+                        passes
+                    )),
+                ]),
+            ],
+            GameEvent::WaitingForDice { needed } => vec![
+                Log::public(vec![
+                    N::text("Waiting for "),
+                    bold(format!("{}", needed)),
+                    N::text(" dice to be entered with the roll command"),
+                ]),
+            ],
+            GameEvent::DieEntered { player, value } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" entered a roll of "),
+                    bold(format!("{}", value)),
+                ]),
+            ],
+            GameEvent::NewBoss {
+                player,
+                loc,
+                casino,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" is now the boss of "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                ]),
+            ],
+            GameEvent::Gambled {
+                player,
+                boss,
+                loc,
+                casino,
+                amount,
+                roll,
+                house_roll,
+                won,
+            } => vec![
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(" gambled "),
+                    render_cash(amount),
+                    N::text(" at "),
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(", rolling "),
+                    bold(format!("{}", roll)),
+                    N::text(" against the house's "),
+                    bold(format!("{}", house_roll)),
+                ]),
+                Log::public(vec![
+                    N::Player(player),
+                    N::text(if won { " won " } else { " lost " }),
+                    render_cash(amount),
+                    N::text(if won { " from " } else { " to " }),
+                    N::Player(boss),
+                ]),
+            ],
+            GameEvent::CasinoPayout { casino, cards_left } => vec![
+                Log::public(vec![
+                    casino.render(),
+                    N::text(" casinos pay out, "),
+                    bold(format!("{}", cards_left)),
+                    N::text(" cards remaining"),
+                ]),
+            ],
+            GameEvent::StripPayout => vec![
+                Log::public(vec![bold("The Strip".to_string()), N::text(" pays out")]),
+            ],
+            GameEvent::CasinoPaid {
+                player,
+                loc,
+                casino,
+                cash,
+                points,
+            } => vec![
+                Log::public(vec![
+                    casino.render(),
+                    N::text(" at "),
+                    loc.render(),
+                    N::text(" paid "),
+                    N::Player(player),
+                    N::text(" "),
+                    render_cash(cash),
+                    N::text(" and "),
+                    bold(format!("{}", points)),
+                    N::text(" points"),
+                ]),
+            ],
+            GameEvent::CashTransferred { .. } | GameEvent::PointsAwarded { .. } => vec![],
+            GameEvent::FinalScoring => vec![
+                Log::public(vec![bold("Final scoring, all casinos pay out".to_string())]),
+            ],
+            GameEvent::GameEnded => vec![Log::public(vec![bold("The game is over".to_string())])],
+        }
+    }
+}
@@ -6,35 +6,46 @@ extern crate rand;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
 
 extern crate brdgme_color;
 extern crate brdgme_game;
 extern crate brdgme_markup;
 
-#[cfg(test)]
-extern crate serde_json;
-
 use rand::Rng;
 
+use std::cmp;
+
 use brdgme_game::{CommandResponse, Gamer, Log, Status};
 use brdgme_game::game::gen_placings;
 use brdgme_game::errors::GameError;
 use brdgme_game::command::Spec as CommandSpec;
-use brdgme_markup::Node as N;
 
 pub mod board;
 pub mod tile;
 pub mod casino;
 pub mod render;
 pub mod card;
+pub mod stats;
+pub mod rng;
+pub mod dice;
+pub mod supply;
+pub mod event;
+pub mod replay;
+pub mod save;
+pub mod validate;
 mod command;
 
-use board::{Board, BoardTile, Loc, TileOwner};
-use casino::Casino;
-use tile::TILES;
-use card::{render_cards, shuffled_deck, Card};
-use render::render_cash;
-use command::Command;
+use board::{Board, BoardCasino, BoardTile, CasinoTile, Loc, TileOwner};
+use casino::{Casino, CASINOS};
+use tile::{Payout, TILES};
+use card::{casino_card_count, shuffled_deck, Card};
+pub use command::Command;
+use stats::PlayerStats;
+use rng::GameRng;
+use dice::{Dice, DiceSource, FairDice};
+use event::GameEvent;
+use save::SAVE_VERSION;
 
 pub const STARTING_CARDS: usize = 2;
 pub const PLAYER_DICE: usize = 12;
@@ -42,6 +53,12 @@ pub const PLAYER_OWNER_TOKENS: usize = 10;
 pub const CASINO_CARDS: usize = 9;
 pub const CASINO_TILES: usize = 9;
 pub const CASINO_DEFAULT_HEIGHT: usize = 1;
+pub const CASINO_MAX_HEIGHT: usize = 5;
+pub const REMODEL_COST_PER_TILE: usize = 5;
+pub const REORG_COST_PER_DIE: usize = 1;
+pub const GAMBLE_MAX_PER_TILE: usize = 5;
+pub const RAISE_COST_PER_TILE: usize = 5;
+pub const GAMBLE_DICE: usize = 2;
 
 pub const DIE_MIN: usize = 1;
 pub const DIE_MAX: usize = 6;
@@ -51,14 +68,36 @@ pub static POINT_STOPS: &'static [usize] = &[
     73, 81, 90,
 ];
 
+/// Where the game is within a turn. The draw and payout phases run automatically at the start of
+/// each turn, so commands are only accepted during the actions phase, or while waiting for dice.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum Phase {
+    Draw,
+    Payout,
+    Actions,
+    /// A boss tie needs more dice than have been entered for physical dice, so the game waits
+    /// for them to be entered with the roll command before continuing with actions.
+    AwaitingDice,
+    GameOver,
+}
+
+// Blank games are waiting on actions.
+impl Default for Phase {
+    fn default() -> Self {
+        Phase::Actions
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PubState {
-    pub players: Vec<Player>,
+    pub players: Vec<PubPlayer>,
     pub current_player: usize,
     pub remaining_deck: usize,
+    pub casino_cards_left: Vec<(Casino, usize)>,
     pub played: Vec<Card>,
     pub board: Board,
     pub finished: bool,
+    pub phase: Phase,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -72,20 +111,52 @@ pub struct PlayerState {
 pub struct Player {
     pub cash: usize,
     pub points: usize,
+    pub stats: PlayerStats,
+    pub hand: Vec<Card>,
 }
 
+/// The public view of a player, which only shows how many cards are in their hand.
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PubPlayer {
+    pub cash: usize,
+    pub points: usize,
+    pub stats: PlayerStats,
+    pub hand_size: usize,
+}
+
+impl<'a> From<&'a Player> for PubPlayer {
+    fn from(p: &'a Player) -> Self {
+        PubPlayer {
+            cash: p.cash,
+            points: p.points,
+            stats: p.stats.clone(),
+            hand_size: p.hand.len(),
+        }
+    }
+}
+
+/// Deserialising a game migrates it from older save versions, see `save`.
+#[derive(Serialize, Default, Debug, Clone)]
 pub struct Game {
+    /// The save layout version, see `save::migrate`.
+    pub version: usize,
+    /// The seed the game was created with, so a replay can be built from a live game.
+    pub seed: u64,
     pub players: Vec<Player>,
     pub current_player: usize,
     pub deck: Vec<Card>,
+    /// Cards which have been drawn but aren't held by a player, which is only the game end card.
+    /// Lot cards stay private in the hand of the player who drew them.
     pub played: Vec<Card>,
     pub board: Board,
     pub finished: bool,
-}
-
-pub fn roll() -> usize {
-    rand::thread_rng().gen::<usize>() % (DIE_MAX - DIE_MIN) + DIE_MIN
+    pub phase: Phase,
+    pub gambled: bool,
+    pub dice: DiceSource,
+    /// Events from the most recent command, or from setup before the first command. They aren't
+    /// saved, as they include private events such as cards being drawn.
+    #[serde(skip)]
+    events: Vec<GameEvent>,
 }
 
 impl Gamer for Game {
@@ -93,68 +164,22 @@ impl Gamer for Game {
     type PlayerState = PlayerState;
 
     fn new(players: usize) -> Result<(Self, Vec<Log>), GameError> {
-        if players < 2 || players > 6 {
-            return Err(GameError::PlayerCount {
-                min: 2,
-                max: 6,
-                given: players,
-            });
-        }
-        let mut logs: Vec<Log> = vec![];
-        let mut board = Board::default();
-        let mut deck = shuffled_deck(players);
-        let mut played: Vec<Card> = vec![];
-        let current_player = rand::thread_rng().gen::<usize>() % players;
-        let players: Vec<Player> = (0..players)
-            .map(|p| {
-                let cards: Vec<Card> = deck.drain(..STARTING_CARDS).collect();
-                let cash = cards.iter().fold(0, |acc, c| match *c {
-                    Card::Loc { loc } => {
-                        board.set(loc, BoardTile::Owned { player: p });
-                        acc + TILES[&loc].starting_cash
-                    }
-                    Card::GameEnd => unreachable!(),
-                });
-                logs.push(Log::public(vec![
-                    N::Player(p),
-                    N::text(" drew "),
-                    N::Group(render_cards(&cards)),
-                    N::text(" and will start with "),
-                    render_cash(cash),
-                ]));
-                let player = Player {
-                    cash,
-                    ..Player::default()
-                };
-                played.extend(cards);
-                player
-            })
-            .collect();
-        logs.push(Log::public(vec![
-            N::Player(current_player),
-            N::text(" will start the game"),
-        ]));
-        Ok((
-            Game {
-                players,
-                current_player,
-                board,
-                deck,
-                played,
-                finished: false,
-            },
-            logs,
-        ))
+        Game::with_seed(players, rand::thread_rng().gen())
     }
 
     fn pub_state(&self) -> Self::PubState {
         PubState {
-            players: self.players.clone(),
+            players: self.players.iter().map(PubPlayer::from).collect(),
             current_player: self.current_player,
             remaining_deck: self.deck.len(),
+            casino_cards_left: CASINOS
+                .iter()
+                .map(|c| (*c, casino_card_count(&self.deck, c)))
+                .collect(),
             played: self.played.clone(),
             board: self.board.clone(),
             finished: self.finished,
+            phase: self.phase,
         }
     }
 
@@ -166,21 +191,29 @@ impl Gamer for Game {
         }
     }
 
-    #[allow(unused_variables)]
     fn command(
         &mut self,
         player: usize,
         input: &str,
         players: &[String],
     ) -> Result<CommandResponse, GameError> {
+        if self.finished || self.phase == Phase::GameOver {
+            return Err(GameError::InvalidInput {
+                message: "the game is already finished".to_string(),
+            });
+        }
         let output = self.command_parser(player).parse(input, players)?;
+        self.events.clear();
+        // Every action checks it is allowed before changing anything, and rolling dice can't
+        // fail, so a failed command leaves the game untouched.
         let (logs, can_undo) = match output.value {
             Command::Build { loc, casino } => self.build(player, &loc, &casino)?,
-            Command::Remodel { loc, casino } => unimplemented!(),
-            Command::Reorg { loc } => unimplemented!(),
-            Command::Sprawl { from, to } => unimplemented!(),
-            Command::Gamble { player, amount } => unimplemented!(),
-            Command::Raise { loc } => unimplemented!(),
+            Command::Remodel { loc, casino } => self.remodel(player, &loc, &casino)?,
+            Command::Reorg { loc } => self.reorg(player, &loc)?,
+            Command::Sprawl { from, to } => self.sprawl(player, &from, &to)?,
+            Command::Gamble { loc, amount } => self.gamble(player, &loc, amount)?,
+            Command::Raise { loc } => self.raise(player, &loc)?,
+            Command::Roll { value } => self.enter_die(player, value)?,
             Command::Done => self.done(player)?,
         };
         Ok(CommandResponse {
@@ -197,7 +230,7 @@ impl Gamer for Game {
                     .iter()
                     .map(|p| vec![p.points as i32, p.cash as i32])
                     .collect::<Vec<Vec<i32>>>()),
-                stats: vec![],
+                stats: self.players.iter().map(|p| p.stats.to_stats()).collect(),
             }
         } else {
             Status::Active {
@@ -228,8 +261,103 @@ impl Gamer for Game {
 }
 
 impl Game {
+    /// Creates a new game where every random decision is derived from the seed, so games with the
+    /// same seed and commands are identical.
+    pub fn with_seed(players: usize, seed: u64) -> Result<(Self, Vec<Log>), GameError> {
+        if players < 2 || players > 6 {
+            return Err(GameError::PlayerCount {
+                min: 2,
+                max: 6,
+                given: players,
+            });
+        }
+        let mut logs: Vec<Log> = vec![];
+        let mut rng = GameRng::from_seed(seed);
+        let mut game = Game {
+            version: SAVE_VERSION,
+            seed,
+            players: vec![Player::default(); players],
+            current_player: rng.gen::<usize>() % players,
+            deck: shuffled_deck(players, &mut rng),
+            dice: DiceSource::Fair(FairDice::from_seed(rng.gen())),
+            ..Game::default()
+        };
+        for p in 0..players {
+            let cards: Vec<Card> = game.deck.drain(..STARTING_CARDS).collect();
+            let cash = cards.iter().fold(0, |acc, c| match *c {
+                Card::Loc { loc } => {
+                    game.claim_lot(p, &loc);
+                    acc + TILES[&loc].starting_cash
+                }
+                Card::GameEnd => unreachable!(),
+            });
+            game.players[p].cash = cash;
+            game.players[p].hand.extend(cards.clone());
+            logs.extend(game.emit(GameEvent::CardsDealt { player: p, cards }));
+            logs.extend(game.emit(GameEvent::StartingCash {
+                player: p,
+                amount: cash,
+            }));
+        }
+        let current_player = game.current_player;
+        logs.extend(game.emit(GameEvent::StartingPlayer {
+            player: current_player,
+        }));
+        logs.extend(game.start_turn());
+        Ok((game, logs))
+    }
+
+    /// Loads a saved game, migrating it from older save versions.
+    pub fn load(json: &str) -> Result<Self, GameError> {
+        save::load(json)
+    }
+
+    /// The events from the most recent command, or from setup if no command has been run yet.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Records an event, returning the logs generated from it.
+    fn emit(&mut self, event: GameEvent) -> Vec<Log> {
+        let logs = event.logs();
+        self.events.push(event);
+        logs
+    }
+
+    fn emit_all(&mut self, events: Vec<GameEvent>) -> Vec<Log> {
+        let mut logs: Vec<Log> = vec![];
+        for e in events {
+            logs.extend(self.emit(e));
+        }
+        logs
+    }
+
+    /// Resolves any boss ties on the board, returning the logs and whether any ties were found.
+    /// Every action which changes the board finishes with this, so it also records the rerolled
+    /// dice and the biggest casinos in the player stats.
+    fn resolve_boss_ties(&mut self) -> (Vec<Log>, bool) {
+        let events = self.board.resolve_boss_ties(&mut self.dice);
+        let tied = !events.is_empty();
+        self.phase = match events.last() {
+            Some(&GameEvent::WaitingForDice { .. }) => Phase::AwaitingDice,
+            _ => Phase::Actions,
+        };
+        for e in &events {
+            if let GameEvent::DieRerolled { player, .. } = *e {
+                self.players[player].stats.dice_rolled += 1;
+            }
+        }
+        self.update_biggest_casinos();
+        (self.emit_all(events), tied)
+    }
+
+    /// Whether it's the player's turn and the game is waiting on them to take actions.
+    fn can_act(&self, player: usize) -> bool {
+        player == self.current_player && self.phase == Phase::Actions
+    }
+
     fn can_build(&self, player: usize) -> bool {
-        player == self.current_player
+        self.can_act(player)
     }
 
     fn build(
@@ -262,12 +390,15 @@ impl Game {
                 })
             }
         }
+        self.board.check_dice(p)?;
+        self.board.check_casino_tiles(casino, 1)?;
         if self.players[p].cash < TILES[loc].build_cost {
             return Err(GameError::InvalidInput {
                 message: "you don't have enough cash".to_string(),
             });
         }
         self.players[p].cash -= TILES[loc].build_cost;
+        self.players[p].stats.tiles_built += 1;
         self.board.set(
             *loc,
             BoardTile::Built {
@@ -279,28 +410,460 @@ impl Game {
                 height: CASINO_DEFAULT_HEIGHT,
             },
         );
-        let mut logs: Vec<Log> = vec![
-            Log::public(vec![
-                N::Player(p),
-                N::text(" built "),
-                casino.render(),
-                N::text(" at "),
-                loc.render(),
-            ]),
-        ];
-        let mut can_undo = true;
+        let mut logs = self.emit(GameEvent::CasinoBuilt {
+            player: p,
+            loc: *loc,
+            casino: *casino,
+            cost: TILES[loc].build_cost,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(p),
+            to: None,
+            amount: TILES[loc].build_cost,
+        }));
 
         // Building can trigger boss ties.
-        if let Some(resolve_logs) = self.board.resolve_boss_ties() {
-            logs.extend(resolve_logs);
-            can_undo = false;
+        let (resolve_logs, tied) = self.resolve_boss_ties();
+        logs.extend(resolve_logs);
+
+        Ok((logs, !tied))
+    }
+
+    fn can_sprawl(&self, player: usize) -> bool {
+        self.can_act(player)
+    }
+
+    fn sprawl(&mut self, p: usize, from: &Loc, to: &Loc) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_sprawl(p) {
+            return Err(GameError::InvalidInput {
+                message: "can't sprawl at the moment".to_string(),
+            });
+        }
+
+        if !TILES.contains_key(to) {
+            return Err(GameError::InvalidInput {
+                message: "not a valid location".to_string(),
+            });
+        }
+        let bc = match self.board.casino_at(from) {
+            Some(bc) => bc,
+            None => {
+                return Err(GameError::InvalidInput {
+                    message: "there isn't a casino at that location".to_string(),
+                })
+            }
+        };
+        if bc.boss() != Some(p) {
+            return Err(GameError::InvalidInput {
+                message: "you aren't the boss of that casino".to_string(),
+            });
+        }
+        match self.board.get(to) {
+            BoardTile::Unowned => {}
+            _ => {
+                return Err(GameError::InvalidInput {
+                    message: "you can only sprawl to an empty lot".to_string(),
+                })
+            }
+        }
+        if !bc.tiles.iter().any(|t| t.loc.neighbours().contains(to)) {
+            return Err(GameError::InvalidInput {
+                message: "that location isn't adjacent to the casino".to_string(),
+            });
         }
+        self.board.check_dice(p)?;
+        self.board.check_casino_tiles(&bc.casino, 1)?;
+        if self.players[p].cash < TILES[to].build_cost {
+            return Err(GameError::InvalidInput {
+                message: "you don't have enough cash".to_string(),
+            });
+        }
+        self.players[p].cash -= TILES[to].build_cost;
+        self.players[p].stats.tiles_sprawled += 1;
+        self.board.set(
+            *to,
+            BoardTile::Built {
+                casino: bc.casino,
+                owner: Some(TileOwner {
+                    die: TILES[to].die,
+                    player: p,
+                }),
+                height: bc.height,
+            },
+        );
+        let mut logs = self.emit(GameEvent::CasinoSprawled {
+            player: p,
+            from: *from,
+            to: *to,
+            casino: bc.casino,
+            cost: TILES[to].build_cost,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(p),
+            to: None,
+            amount: TILES[to].build_cost,
+        }));
+
+        // Sprawling can merge casinos and trigger boss ties.
+        let (resolve_logs, tied) = self.resolve_boss_ties();
+        logs.extend(resolve_logs);
 
-        Ok((logs, can_undo))
+        Ok((logs, !tied))
+    }
+
+    fn can_remodel(&self, player: usize) -> bool {
+        self.can_act(player)
+    }
+
+    fn remodel(
+        &mut self,
+        p: usize,
+        loc: &Loc,
+        casino: &Casino,
+    ) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_remodel(p) {
+            return Err(GameError::InvalidInput {
+                message: "can't remodel at the moment".to_string(),
+            });
+        }
+
+        let bc = match self.board.casino_at(loc) {
+            Some(bc) => bc,
+            None => {
+                return Err(GameError::InvalidInput {
+                    message: "there isn't a casino at that location".to_string(),
+                })
+            }
+        };
+        if bc.boss() != Some(p) {
+            return Err(GameError::InvalidInput {
+                message: "you aren't the boss of that casino".to_string(),
+            });
+        }
+        if bc.casino == *casino {
+            return Err(GameError::InvalidInput {
+                message: format!("the casino is already {}", casino),
+            });
+        }
+        self.board.check_casino_tiles(casino, bc.tiles.len())?;
+        let cost = bc.tiles.len() * REMODEL_COST_PER_TILE;
+        if self.players[p].cash < cost {
+            return Err(GameError::InvalidInput {
+                message: "you don't have enough cash".to_string(),
+            });
+        }
+        self.players[p].cash -= cost;
+        for t in &bc.tiles {
+            self.board.set(
+                t.loc,
+                BoardTile::Built {
+                    casino: *casino,
+                    owner: t.owner,
+                    height: bc.height,
+                },
+            );
+        }
+        let mut logs = self.emit(GameEvent::CasinoRemodeled {
+            player: p,
+            loc: bc.loc(),
+            from: bc.casino,
+            to: *casino,
+            cost,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(p),
+            to: None,
+            amount: cost,
+        }));
+
+        // The new colour can merge the casino with neighbouring casinos.
+        let (resolve_logs, tied) = self.resolve_boss_ties();
+        logs.extend(resolve_logs);
+
+        Ok((logs, !tied))
+    }
+
+    fn can_reorg(&self, player: usize) -> bool {
+        self.can_act(player)
+    }
+
+    fn reorg(&mut self, p: usize, loc: &Loc) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_reorg(p) {
+            return Err(GameError::InvalidInput {
+                message: "can't reorg at the moment".to_string(),
+            });
+        }
+
+        let bc = match self.board.casino_at(loc) {
+            Some(bc) => bc,
+            None => {
+                return Err(GameError::InvalidInput {
+                    message: "there isn't a casino at that location".to_string(),
+                })
+            }
+        };
+        if !bc.has_player(p) {
+            return Err(GameError::InvalidInput {
+                message: "you don't have a die in that casino".to_string(),
+            });
+        }
+        let owned: Vec<CasinoTile> = bc.tiles
+            .iter()
+            .filter(|t| t.owner.is_some())
+            .cloned()
+            .collect();
+        let old_boss = bc.boss();
+        let cost = owned.len() * REORG_COST_PER_DIE;
+        if self.players[p].cash < cost {
+            return Err(GameError::InvalidInput {
+                message: "you don't have enough cash".to_string(),
+            });
+        }
+        self.check_dice_available(owned.len())?;
+        self.players[p].cash -= cost;
+        let mut logs = self.emit(GameEvent::CasinoReorganised {
+            player: p,
+            loc: bc.loc(),
+            casino: bc.casino,
+            cost,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(p),
+            to: None,
+            amount: cost,
+        }));
+        for t in owned {
+            if let (Some(TileOwner { player, die: from }), Some(to)) =
+                (t.owner, self.board.reroll_at(&t.loc, &mut self.dice))
+            {
+                self.players[player].stats.dice_rolled += 1;
+                logs.extend(self.emit(GameEvent::DieRerolled {
+                    player,
+                    loc: t.loc,
+                    from,
+                    to,
+                }));
+            }
+        }
+        let (resolve_logs, _) = self.resolve_boss_ties();
+        logs.extend(resolve_logs);
+        let boss = self.board.casino_at(loc).and_then(|bc| bc.boss());
+        if let Some(player) = boss {
+            if boss != old_boss {
+                logs.extend(self.emit(GameEvent::NewBoss {
+                    player,
+                    loc: bc.loc(),
+                    casino: bc.casino,
+                }));
+            }
+        }
+
+        Ok((logs, false))
+    }
+
+    fn can_gamble(&self, player: usize) -> bool {
+        self.can_act(player) && !self.gambled
+    }
+
+    /// The most that can be gambled at a casino, limited by the casino size and the cash of both
+    /// the gambler and the boss.
+    fn gamble_max(&self, p: usize, bc: &BoardCasino) -> usize {
+        let mut max = cmp::min(bc.tiles.len() * GAMBLE_MAX_PER_TILE, self.players[p].cash);
+        if let Some(boss) = bc.boss() {
+            max = cmp::min(max, self.players[boss].cash);
+        }
+        max
+    }
+
+    fn gamble(
+        &mut self,
+        p: usize,
+        loc: &Loc,
+        amount: usize,
+    ) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_gamble(p) {
+            return Err(GameError::InvalidInput {
+                message: "can't gamble at the moment".to_string(),
+            });
+        }
+
+        let bc = match self.board.casino_at(loc) {
+            Some(bc) => bc,
+            None => {
+                return Err(GameError::InvalidInput {
+                    message: "there isn't a casino at that location".to_string(),
+                })
+            }
+        };
+        let boss = match bc.boss() {
+            Some(boss) if boss != p => boss,
+            _ => {
+                return Err(GameError::InvalidInput {
+                    message: "you can only gamble at an opponent's casino".to_string(),
+                })
+            }
+        };
+        if amount == 0 {
+            return Err(GameError::InvalidInput {
+                message: "you must gamble at least $1".to_string(),
+            });
+        }
+        let max = self.gamble_max(p, &bc);
+        if amount > max {
+            return Err(GameError::InvalidInput {
+                message: format!("you can gamble at most ${} at that casino", max),
+            });
+        }
+        self.check_dice_available(GAMBLE_DICE * 2)?;
+        self.gambled = true;
+        let mut gambler_roll = 0;
+        let mut house_roll = 0;
+        for _ in 0..GAMBLE_DICE {
+            gambler_roll += self.dice.roll();
+            house_roll += self.dice.roll();
+        }
+        self.players[p].stats.dice_rolled += GAMBLE_DICE;
+        self.players[boss].stats.dice_rolled += GAMBLE_DICE;
+        // Ties go to the house.
+        let won = gambler_roll > house_roll;
+        let (winner, loser) = if won { (p, boss) } else { (boss, p) };
+        self.players[loser].cash -= amount;
+        self.players[winner].cash += amount;
+        self.players[winner].stats.gamble_won += amount;
+        self.players[loser].stats.gamble_lost += amount;
+        let mut logs = self.emit(GameEvent::Gambled {
+            player: p,
+            boss,
+            loc: bc.loc(),
+            casino: bc.casino,
+            amount,
+            roll: gambler_roll,
+            house_roll,
+            won,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(loser),
+            to: Some(winner),
+            amount,
+        }));
+
+        Ok((logs, false))
+    }
+
+    fn can_raise(&self, player: usize) -> bool {
+        self.can_act(player)
+    }
+
+    fn raise(&mut self, p: usize, loc: &Loc) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_raise(p) {
+            return Err(GameError::InvalidInput {
+                message: "can't raise at the moment".to_string(),
+            });
+        }
+
+        let bc = match self.board.casino_at(loc) {
+            Some(bc) => bc,
+            None => {
+                return Err(GameError::InvalidInput {
+                    message: "there isn't a casino at that location".to_string(),
+                })
+            }
+        };
+        if bc.boss() != Some(p) {
+            return Err(GameError::InvalidInput {
+                message: "you aren't the boss of that casino".to_string(),
+            });
+        }
+        if bc.height >= CASINO_MAX_HEIGHT {
+            return Err(GameError::InvalidInput {
+                message: "that casino is already as high as it can go".to_string(),
+            });
+        }
+        let cost = bc.tiles.len() * RAISE_COST_PER_TILE;
+        if self.players[p].cash < cost {
+            return Err(GameError::InvalidInput {
+                message: "you don't have enough cash".to_string(),
+            });
+        }
+        self.players[p].cash -= cost;
+        let height = bc.height + 1;
+        for t in &bc.tiles {
+            self.board.set(
+                t.loc,
+                BoardTile::Built {
+                    casino: bc.casino,
+                    owner: t.owner,
+                    height,
+                },
+            );
+        }
+        let mut logs = self.emit(GameEvent::CasinoRaised {
+            player: p,
+            loc: bc.loc(),
+            casino: bc.casino,
+            height,
+            cost,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: Some(p),
+            to: None,
+            amount: cost,
+        }));
+
+        // The new height can merge the casino with neighbouring casinos of the same height.
+        let (resolve_logs, tied) = self.resolve_boss_ties();
+        logs.extend(resolve_logs);
+
+        Ok((logs, !tied))
+    }
+
+    /// Checks enough dice are available for an action before it changes anything, which only
+    /// fails for physical or scripted dice.
+    fn check_dice_available(&self, count: usize) -> Result<(), GameError> {
+        if self.dice.has(count) {
+            return Ok(());
+        }
+        Err(GameError::InvalidInput {
+            message: match self.dice {
+                DiceSource::Physical(_) => format!(
+                    "{} dice are needed, enter them with the roll command first",
+                    count
+                ),
+                _ => format!("{} dice are needed but the dice have run out", count),
+            },
+        })
+    }
+
+    /// Physical dice are entered by whoever's turn it is, acting as the moderator, either ahead
+    /// of an action which rolls dice or when the game is waiting for dice.
+    fn can_roll(&self, player: usize) -> bool {
+        let physical = match self.dice {
+            DiceSource::Physical(_) => true,
+            _ => false,
+        };
+        physical && player == self.current_player
+            && (self.phase == Phase::Actions || self.phase == Phase::AwaitingDice)
+    }
+
+    fn enter_die(&mut self, player: usize, value: usize) -> Result<(Vec<Log>, bool), GameError> {
+        if !self.can_roll(player) {
+            return Err(GameError::InvalidInput {
+                message: "can't enter dice at the moment".to_string(),
+            });
+        }
+        if let DiceSource::Physical(ref mut dice) = self.dice {
+            dice.enter(value)?;
+        }
+        let mut logs = self.emit(GameEvent::DieEntered { player, value });
+        if self.phase == Phase::AwaitingDice {
+            let (resolve_logs, _) = self.resolve_boss_ties();
+            logs.extend(resolve_logs);
+        }
+        Ok((logs, false))
     }
 
     fn can_done(&self, player: usize) -> bool {
-        player == self.current_player
+        self.can_act(player)
     }
 
     fn done(&mut self, player: usize) -> Result<(Vec<Log>, bool), GameError> {
@@ -315,7 +878,160 @@ impl Game {
 
     fn next_player(&mut self) -> (Vec<Log>, bool) {
         self.current_player = (self.current_player + 1) % self.players.len();
-        (vec![], false)
+        self.gambled = false;
+        (self.start_turn(), false)
+    }
+
+    /// Places an owner token for the player on a lot, returning false if the lot has already been
+    /// claimed or built on.
+    fn claim_lot(&mut self, p: usize, loc: &Loc) -> bool {
+        match self.board.get(loc) {
+            BoardTile::Unowned => {
+                self.board.set(*loc, BoardTile::Owned { player: p });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Runs the automatic draw and payout phases at the start of a turn, leaving the game in the
+    /// actions phase unless the game end card was drawn.
+    fn start_turn(&mut self) -> Vec<Log> {
+        self.phase = Phase::Draw;
+        let (mut logs, drawn) = self.draw();
+        if let Some(loc) = drawn {
+            logs.extend(self.payout(&loc));
+            self.phase = Phase::Actions;
+        }
+        logs
+    }
+
+    /// Draws the top card of the deck for the current player. Starting cash is only paid for the
+    /// cards dealt during setup, so drawing a lot during the game only claims it. Returns the lot
+    /// which was drawn, which is then paid out.
+    fn draw(&mut self) -> (Vec<Log>, Option<Loc>) {
+        if self.deck.is_empty() {
+            self.phase = Phase::Actions;
+            return (vec![], None);
+        }
+        let p = self.current_player;
+        let card = self.deck.remove(0);
+        let mut logs = self.emit(GameEvent::CardDrawn {
+            player: p,
+            card: card.clone(),
+        });
+        let drawn = match card {
+            Card::Loc { loc } => {
+                let event = match self.board.check_owner_tokens(p) {
+                    Ok(()) if self.claim_lot(p, &loc) => GameEvent::TileClaimed { player: p, loc },
+                    Ok(()) => GameEvent::LotAlreadyBuilt { player: p, loc },
+                    Err(_) => GameEvent::OutOfOwnerTokens { player: p },
+                };
+                logs.extend(self.emit(event));
+                self.players[p].hand.push(card);
+                self.phase = Phase::Payout;
+                Some(loc)
+            }
+            Card::GameEnd => {
+                self.played.push(card);
+                logs.extend(self.end_game());
+                None
+            }
+        };
+        (logs, drawn)
+    }
+
+    /// Pays out the casinos shown on the card for the lot which was just drawn.
+    fn payout(&mut self, loc: &Loc) -> Vec<Log> {
+        match TILES[loc].payout {
+            Payout::Casino(casino) => self.casino_payout(&casino),
+            Payout::Strip => self.strip_payout(),
+        }
+    }
+
+    /// Runs the final scoring round, where every casino on the board pays out, and ends the game.
+    fn end_game(&mut self) -> Vec<Log> {
+        let mut logs = self.emit(GameEvent::FinalScoring);
+        for bc in self.board.casinos() {
+            logs.extend(self.pay_boss(&bc));
+        }
+        self.finished = true;
+        self.phase = Phase::GameOver;
+        logs.extend(self.emit(GameEvent::GameEnded));
+        logs
+    }
+
+    /// Pays out every casino of the given colour.
+    fn casino_payout(&mut self, casino: &Casino) -> Vec<Log> {
+        let cards_left = casino_card_count(&self.deck, casino);
+        let mut logs = self.emit(GameEvent::CasinoPayout {
+            casino: *casino,
+            cards_left,
+        });
+        for bc in self.board.casinos() {
+            if bc.casino == *casino {
+                logs.extend(self.pay_boss(&bc));
+            }
+        }
+        logs
+    }
+
+    /// Pays out every casino with a tile on the strip, regardless of colour.
+    fn strip_payout(&mut self) -> Vec<Log> {
+        let mut logs = self.emit(GameEvent::StripPayout);
+        for bc in self.board.casinos() {
+            if bc.on_strip() {
+                logs.extend(self.pay_boss(&bc));
+            }
+        }
+        logs
+    }
+
+    /// Pays the boss of a casino cash and points based on the casino's size and height.
+    fn pay_boss(&mut self, bc: &BoardCasino) -> Vec<Log> {
+        let boss = match bc.boss() {
+            Some(boss) => boss,
+            None => return vec![],
+        };
+        let value = bc.tiles.len() * bc.height;
+        self.players[boss].cash += value;
+        self.players[boss].stats.payout_cash += value;
+        let points = self.add_points(boss, value);
+        let mut logs = self.emit(GameEvent::CasinoPaid {
+            player: boss,
+            loc: bc.loc(),
+            casino: bc.casino,
+            cash: value,
+            points,
+        });
+        logs.extend(self.emit(GameEvent::CashTransferred {
+            from: None,
+            to: Some(boss),
+            amount: value,
+        }));
+        logs.extend(self.emit(GameEvent::PointsAwarded {
+            player: boss,
+            stops: value,
+            points,
+        }));
+        logs
+    }
+
+    fn update_biggest_casinos(&mut self) {
+        for bc in self.board.casinos() {
+            if let Some(boss) = bc.boss() {
+                let stats = &mut self.players[boss].stats;
+                stats.biggest_casino = cmp::max(stats.biggest_casino, bc.tiles.len());
+            }
+        }
+    }
+
+    /// Moves a player along the point track, returning the number of points gained.
+    fn add_points(&mut self, p: usize, stops: usize) -> usize {
+        let from = self.players[p].points;
+        let to = cmp::min(from + stops, POINT_STOPS.len() - 1);
+        self.players[p].points = to;
+        POINT_STOPS[to] - POINT_STOPS[from]
     }
 }
 
@@ -328,6 +1044,650 @@ mod tests {
         assert_eq!(Game::player_counts(), vec![2, 3, 4, 5, 6]);
     }
 
+    #[test]
+    fn new_draws_for_first_player() {
+        let game = Game::new(3)
+            .expect("could not create game with 3 players")
+            .0;
+        assert_eq!(
+            STARTING_CARDS + 1,
+            game.players[game.current_player].hand.len()
+        );
+        assert_eq!(
+            STARTING_CARDS + 1,
+            game.board.player_locs(game.current_player).len()
+        );
+    }
+
+    fn blank_game(players: usize) -> Game {
+        Game {
+            players: vec![Player::default(); players],
+            ..Game::default()
+        }
+    }
+
+    #[test]
+    fn casino_payout_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Albion,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: 1,
+            },
+        );
+        game.board.set(
+            (Block::A, 2).into(),
+            BoardTile::Built {
+                casino: Casino::Albion,
+                owner: Some(TileOwner { die: 2, player: 1 }),
+                height: 1,
+            },
+        );
+        game.board.set(
+            (Block::B, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Sphinx,
+                owner: Some(TileOwner { die: 5, player: 1 }),
+                height: 1,
+            },
+        );
+        game.casino_payout(&Casino::Albion);
+        assert_eq!(2, game.players[0].cash);
+        assert_eq!(2, game.players[0].points);
+        assert_eq!(0, game.players[1].cash);
+        assert_eq!(0, game.players[1].points);
+    }
+
+    #[test]
+    fn strip_payout_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        // A3 is on the strip, A1 isn't.
+        game.board.set(
+            (Block::A, 3).into(),
+            BoardTile::Built {
+                casino: Casino::Vega,
+                owner: Some(TileOwner { die: 5, player: 0 }),
+                height: 1,
+            },
+        );
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 1 }),
+                height: 1,
+            },
+        );
+        game.strip_payout();
+        assert_eq!(1, game.players[0].cash);
+        assert_eq!(0, game.players[1].cash);
+    }
+
+    #[test]
+    fn sprawl_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 20;
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: 1,
+            },
+        );
+        assert!(
+            game.sprawl(0, &(Block::A, 1).into(), &(Block::A, 3).into())
+                .is_err()
+        );
+        assert!(
+            game.sprawl(1, &(Block::A, 1).into(), &(Block::A, 2).into())
+                .is_err()
+        );
+        game.sprawl(0, &(Block::A, 1).into(), &(Block::A, 2).into())
+            .expect("expected sprawl to succeed");
+        assert_eq!(14, game.players[0].cash);
+        assert_eq!(
+            2,
+            game.board
+                .casino_at(&(Block::A, 1).into())
+                .expect("expected casino")
+                .tiles
+                .len()
+        );
+    }
+
+    #[test]
+    fn remodel_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 20;
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: 1,
+            },
+        );
+        assert!(
+            game.remodel(0, &(Block::A, 1).into(), &Casino::Pioneer)
+                .is_err()
+        );
+        game.remodel(0, &(Block::A, 1).into(), &Casino::Vega)
+            .expect("expected remodel to succeed");
+        assert_eq!(20 - REMODEL_COST_PER_TILE, game.players[0].cash);
+        assert_eq!(1, game.board.casino_tile_count(&Casino::Vega));
+        assert_eq!(0, game.board.casino_tile_count(&Casino::Pioneer));
+    }
+
+    #[test]
+    fn remodel_checks_tile_supply() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 100;
+        for lot in 1..CASINO_TILES + 1 {
+            game.board.set(
+                (Block::C, lot).into(),
+                BoardTile::Built {
+                    casino: Casino::Vega,
+                    owner: None,
+                    height: 1,
+                },
+            );
+        }
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: 1,
+            },
+        );
+        assert!(
+            game.remodel(0, &(Block::A, 1).into(), &Casino::Vega)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn gamble_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 20;
+        game.players[1].cash = 20;
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 1 }),
+                height: 1,
+            },
+        );
+        assert!(
+            game.gamble(0, &(Block::A, 1).into(), GAMBLE_MAX_PER_TILE + 1)
+                .is_err()
+        );
+        game.gamble(0, &(Block::A, 1).into(), GAMBLE_MAX_PER_TILE)
+            .expect("expected gamble to succeed");
+        assert_eq!(40, game.players[0].cash + game.players[1].cash);
+        assert!(
+            game.gamble(0, &(Block::A, 1).into(), 1)
+                .is_err(),
+            "expected only one gamble per turn"
+        );
+    }
+
+    #[test]
+    fn raise_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 20;
+        for lot in 1..3 {
+            game.board.set(
+                (Block::A, lot).into(),
+                BoardTile::Built {
+                    casino: Casino::Pioneer,
+                    owner: Some(TileOwner { die: 3, player: 0 }),
+                    height: 1,
+                },
+            );
+        }
+        game.raise(0, &(Block::A, 1).into())
+            .expect("expected raise to succeed");
+        assert_eq!(20 - 2 * RAISE_COST_PER_TILE, game.players[0].cash);
+        let bc = game.board
+            .casino_at(&(Block::A, 2).into())
+            .expect("expected casino");
+        assert_eq!(2, bc.height);
+        assert_eq!(2, bc.tiles.len());
+
+        // Higher casinos pay out more.
+        game.casino_payout(&Casino::Pioneer);
+        assert_eq!(20 - 2 * RAISE_COST_PER_TILE + 4, game.players[0].cash);
+    }
+
+    #[test]
+    fn raise_stops_at_max_height() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 100;
+        let loc: Loc = (Block::A, 1).into();
+        game.board.set(
+            loc,
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 0 }),
+                height: CASINO_MAX_HEIGHT - 1,
+            },
+        );
+        assert!(game.legal_commands(0).contains(&Command::Raise { loc }));
+        game.raise(0, &loc).expect("expected raise to succeed");
+        assert!(game.raise(0, &loc).is_err());
+        assert!(!game.legal_commands(0).contains(&Command::Raise { loc }));
+        assert_eq!(
+            CASINO_MAX_HEIGHT,
+            game.board.casino_at(&loc).expect("expected casino").height
+        );
+    }
+
+    #[test]
+    fn game_end_works() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.deck = vec![Card::GameEnd];
+        game.players[0].cash = 10;
+        game.players[1].cash = 5;
+        game.board.set(
+            (Block::A, 1).into(),
+            BoardTile::Built {
+                casino: Casino::Pioneer,
+                owner: Some(TileOwner { die: 3, player: 1 }),
+                height: 1,
+            },
+        );
+        game.next_player();
+        assert!(game.finished);
+        assert_eq!(Phase::GameOver, game.phase);
+        assert_eq!(1, game.players[1].points);
+        match game.status() {
+            Status::Finished { placings, .. } => assert_eq!(vec![2, 1], placings),
+            _ => panic!("expected game to be finished"),
+        }
+    }
+
+    #[test]
+    fn game_end_reports_stats() {
+        use brdgme_game::Stat;
+
+        let mut game = blank_game(2);
+        game.deck = vec![Card::GameEnd];
+        game.players[0].stats.tiles_built = 3;
+        game.next_player();
+        match game.status() {
+            Status::Finished { stats, .. } => {
+                assert_eq!(2, stats.len());
+                match stats[0].get("Tiles built") {
+                    Some(&Stat::Int(3)) => {}
+                    _ => panic!("expected 3 tiles built"),
+                }
+            }
+            _ => panic!("expected game to be finished"),
+        }
+    }
+
+    #[test]
+    fn game_end_breaks_ties_on_cash() {
+        let mut game = blank_game(2);
+        game.deck = vec![Card::GameEnd];
+        game.players[0].cash = 10;
+        game.players[1].cash = 5;
+        game.next_player();
+        match game.status() {
+            Status::Finished { placings, .. } => assert_eq!(vec![1, 2], placings),
+            _ => panic!("expected game to be finished"),
+        }
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        use serde_json;
+
+        let (mut a, _) = Game::with_seed(3, 1234).expect("could not create game");
+        let (mut b, _) = Game::with_seed(3, 1234).expect("could not create game");
+        assert_eq!(
+            serde_json::to_value(&a).expect("could not serialise game"),
+            serde_json::to_value(&b).expect("could not serialise game")
+        );
+        let names: Vec<String> = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        for _ in 0..5 {
+            let p = a.current_player;
+            a.command(p, "done", &names).expect("could not end turn");
+            b.command(p, "done", &names).expect("could not end turn");
+            assert!(a.validate().is_empty());
+        }
+        assert_eq!(
+            serde_json::to_value(&a).expect("could not serialise game"),
+            serde_json::to_value(&b).expect("could not serialise game")
+        );
+    }
+
+    #[test]
+    fn commands_need_actions_phase() {
+        let mut game = blank_game(2);
+        assert!(game.can_done(0));
+        assert!(!game.can_done(1));
+        game.phase = Phase::Payout;
+        assert!(!game.can_done(0));
+        assert!(game.done(0).is_err());
+    }
+
+    #[test]
+    fn json_without_phase_works() {
+        use serde_json;
+
+        let game = Game::new(2)
+            .expect("could not create game with 2 players")
+            .0;
+        let mut value = serde_json::to_value(&game).expect("could not serialise game to JSON");
+        {
+            // Saves from before phases were tracked also had no version.
+            let obj = value
+                .as_object_mut()
+                .expect("expected game to be an object");
+            obj.remove("phase");
+            obj.remove("version");
+        }
+        let json = serde_json::to_string(&value).expect("could not serialise JSON");
+        // Deserialise the same way the server does, rather than through `Game::load`.
+        let game: Game = serde_json::from_str(&json).expect("could not deserialise game");
+        assert_eq!(Phase::Actions, game.phase);
+    }
+
+    #[test]
+    fn build_checks_dice_supply() {
+        use supply::SupplyError;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 100;
+        let mut locs: Vec<Loc> = TILES.keys().cloned().collect();
+        locs.sort();
+        // Spread the dice over every casino so no casino runs out of tiles first.
+        for (i, loc) in locs.iter().take(PLAYER_DICE).enumerate() {
+            game.board.set(
+                *loc,
+                BoardTile::Built {
+                    casino: CASINOS[i % CASINOS.len()],
+                    owner: Some(TileOwner { die: 1, player: 0 }),
+                    height: 1,
+                },
+            );
+        }
+        let loc = locs[PLAYER_DICE];
+        game.board.set(loc, BoardTile::Owned { player: 0 });
+        assert!(game.validate().is_empty(), "expected the setup to be valid");
+        assert_eq!(
+            Err(SupplyError::Dice { player: 0 }),
+            game.board.check_dice(0)
+        );
+        assert!(game.build(0, &loc, &Casino::Vega).is_err());
+    }
+
+    #[test]
+    fn draw_checks_owner_token_supply() {
+        let mut game = blank_game(2);
+        let mut locs: Vec<Loc> = TILES.keys().cloned().collect();
+        locs.sort();
+        for loc in locs.iter().take(PLAYER_OWNER_TOKENS) {
+            game.board.set(*loc, BoardTile::Owned { player: 0 });
+        }
+        let loc = locs[PLAYER_OWNER_TOKENS];
+        game.deck = vec![Card::Loc { loc }];
+        game.start_turn();
+        match game.board.get(&loc) {
+            BoardTile::Unowned => {}
+            _ => panic!("expected lot to stay unowned"),
+        }
+        assert_eq!(0, game.board.remaining_owner_tokens(0));
+    }
+
+    #[test]
+    fn hands_are_private() {
+        use serde_json;
+
+        let game = Game::new(3)
+            .expect("could not create game with 3 players")
+            .0;
+        let hand = game.players[1].hand.clone();
+        assert!(!hand.is_empty());
+        assert_eq!(
+            hand,
+            game.player_state(1)
+                .state
+                .expect("expected player state")
+                .hand
+        );
+        for &p in &[0, 2] {
+            let other = game.player_state(p)
+                .state
+                .expect("expected player state")
+                .hand;
+            assert!(hand.iter().all(|c| !other.contains(c)));
+        }
+        let pub_state = serde_json::to_value(&game.pub_state()).expect("could not serialise");
+        for (i, p) in pub_state["players"]
+            .as_array()
+            .expect("expected players")
+            .iter()
+            .enumerate()
+        {
+            assert!(p.get("hand").is_none(), "hand shouldn't be public");
+            assert_eq!(Some(game.players[i].hand.len() as u64), p["hand_size"].as_u64());
+        }
+        assert!(game.played.is_empty());
+    }
+
+    #[test]
+    fn reorg_works() {
+        use board::Block;
+        use dice::ScriptedDice;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 3;
+        let a1: Loc = (Block::A, 1).into();
+        let a2: Loc = (Block::A, 2).into();
+        let b1: Loc = (Block::B, 1).into();
+        for &(loc, player, die) in &[(a1, 0, 2), (a2, 1, 5), (b1, 1, 3)] {
+            game.board.set(
+                loc,
+                BoardTile::Built {
+                    casino: Casino::Albion,
+                    owner: Some(TileOwner { die, player }),
+                    height: 1,
+                },
+            );
+        }
+        assert!(game.reorg(0, &b1).is_err(), "player 0 has no die in B1");
+
+        game.dice = DiceSource::Scripted(ScriptedDice::new(vec![6, 1, 5, 2]));
+        game.reorg(0, &a2).expect("expected reorg to succeed");
+        assert_eq!(1, game.players[0].cash);
+        assert_eq!(
+            vec![
+                GameEvent::CasinoReorganised {
+                    player: 0,
+                    loc: a1,
+                    casino: Casino::Albion,
+                    cost: 2,
+                },
+                GameEvent::CashTransferred {
+                    from: Some(0),
+                    to: None,
+                    amount: 2,
+                },
+                GameEvent::DieRerolled {
+                    player: 0,
+                    loc: a1,
+                    from: 2,
+                    to: 6,
+                },
+                GameEvent::DieRerolled {
+                    player: 1,
+                    loc: a2,
+                    from: 5,
+                    to: 1,
+                },
+                GameEvent::NewBoss {
+                    player: 0,
+                    loc: a1,
+                    casino: Casino::Albion,
+                },
+            ],
+            game.events()
+        );
+        assert!(game.reorg(0, &a1).is_err(), "player 0 can't afford another reorg");
+
+        game.players[0].cash = 2;
+        game.events.clear();
+        game.reorg(0, &a1).expect("expected reorg to succeed");
+        assert_eq!(0, game.players[0].cash);
+        assert!(
+            !game.events().iter().any(|e| match *e {
+                GameEvent::NewBoss { .. } => true,
+                _ => false,
+            }),
+            "player 0 stayed the boss"
+        );
+    }
+
+    #[test]
+    fn stats_count_both_sides() {
+        use board::Block;
+        use dice::ScriptedDice;
+
+        let mut game = blank_game(2);
+        let a1: Loc = (Block::A, 1).into();
+        let a2: Loc = (Block::A, 2).into();
+        for &(loc, player) in &[(a1, 0), (a2, 1)] {
+            game.board.set(
+                loc,
+                BoardTile::Built {
+                    casino: Casino::Albion,
+                    owner: Some(TileOwner { die: 3, player }),
+                    height: 1,
+                },
+            );
+        }
+        game.dice = DiceSource::Scripted(ScriptedDice::new(vec![6, 2]));
+        let (_, tied) = game.resolve_boss_ties();
+        assert!(tied);
+        assert_eq!(1, game.players[0].stats.dice_rolled);
+        assert_eq!(1, game.players[1].stats.dice_rolled);
+        let boss = game.board
+            .casino_at(&a1)
+            .and_then(|bc| bc.boss())
+            .expect("expected a boss");
+        assert_eq!(2, game.players[boss].stats.biggest_casino);
+        assert_eq!(0, game.players[1 - boss].stats.biggest_casino);
+
+        // The gambler and the house take turns rolling.
+        let gambler = 1 - boss;
+        game.current_player = gambler;
+        game.players[gambler].cash = 10;
+        game.players[boss].cash = 10;
+        game.dice = DiceSource::Scripted(ScriptedDice::new(vec![6, 1, 6, 1]));
+        game.gamble(gambler, &a1, 5)
+            .expect("expected gamble to succeed");
+        assert_eq!(5, game.players[gambler].stats.gamble_won);
+        assert_eq!(5, game.players[boss].stats.gamble_lost);
+        assert_eq!(0, game.players[gambler].stats.gamble_lost);
+        assert_eq!(0, game.players[boss].stats.gamble_won);
+    }
+
+    #[test]
+    fn build_emits_events() {
+        use board::Block;
+
+        let mut game = blank_game(2);
+        game.players[0].cash = 20;
+        let loc: Loc = (Block::A, 2).into();
+        game.board.set(loc, BoardTile::Owned { player: 0 });
+        game.build(0, &loc, &Casino::Albion)
+            .expect("expected build to succeed");
+        assert_eq!(
+            vec![
+                GameEvent::CasinoBuilt {
+                    player: 0,
+                    loc,
+                    casino: Casino::Albion,
+                    cost: TILES[&loc].build_cost,
+                },
+                GameEvent::CashTransferred {
+                    from: Some(0),
+                    to: None,
+                    amount: TILES[&loc].build_cost,
+                },
+            ],
+            game.events()
+        );
+    }
+
+    #[test]
+    fn events_only_cover_the_last_command() {
+        use serde_json;
+
+        let (mut game, _) = Game::with_seed(2, 8).expect("could not create game");
+        assert!(!game.events().is_empty());
+        let names: Vec<String> = vec!["a".to_string(), "b".to_string()];
+        let p = game.current_player;
+        game.command(p, "done", &names).expect("could not end turn");
+        match game.events().first() {
+            Some(&GameEvent::CardDrawn { player, .. }) => assert_eq!(game.current_player, player),
+            e => panic!("expected the next player's draw to be the first event, got {:?}", e),
+        }
+        let value = serde_json::to_value(&game).expect("could not serialise game");
+        assert!(value.get("events").is_none(), "expected events not to be saved");
+    }
+
+    #[test]
+    fn random_games_stay_valid() {
+        use rand::Rng;
+
+        let names: Vec<String> = (0..4).map(|p| format!("player{}", p + 1)).collect();
+        for seed in 0..3 {
+            let mut rng = GameRng::from_seed(seed);
+            let (mut game, _) = Game::with_seed(4, seed).expect("could not create game");
+            let mut actions = 0;
+            while !game.finished {
+                let p = game.current_player;
+                let legal = game.legal_commands(p);
+                let command = if actions >= 3 {
+                    Command::Done
+                } else {
+                    rng.choose(&legal).expect("expected a legal command").clone()
+                };
+                actions = if command == Command::Done { 0 } else { actions + 1 };
+                game.command(p, &command.to_string(), &names)
+                    .unwrap_or_else(|e| panic!("legal command {} failed: {}", command, e));
+                let violations = game.validate();
+                assert!(violations.is_empty(), "{:?} after {}", violations, command);
+            }
+        }
+    }
+
     #[test]
     fn json_works() {
         use serde_json;
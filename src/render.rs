@@ -7,14 +7,11 @@ use std::iter;
 
 use PubState;
 use PlayerState;
-use board::{Block, Board, BoardTile, Loc, TileOwner, BLOCKS};
+use board::{Block, Board, BoardTile, Loc, Side, TileOwner, BLOCKS, BLOCK_WIDTH};
 use tile::TILES;
 use casino::CASINOS;
-use card::casino_card_count;
-use CASINO_CARDS;
-use CASINO_TILES;
-use PLAYER_DICE;
-use PLAYER_OWNER_TOKENS;
+use card::{render_cards, Card};
+use CASINO_DEFAULT_HEIGHT;
 use POINT_STOPS;
 
 const TILE_WIDTH: usize = 9;
@@ -32,21 +29,38 @@ static UNBUILT_TILE_BG: Color = Color {
     b: 200,
 };
 
+static STRIP_BG: Color = Color {
+    r: 80,
+    g: 80,
+    b: 80,
+};
+
+const STRIP_TEXT: &'static str = "STRIP";
+
 impl Renderer for PubState {
     fn render(&self) -> Vec<N> {
-        vec![self.render_with_perspective(None)]
+        vec![self.render_with_perspective(None, None)]
     }
 }
 
 impl Renderer for PlayerState {
     fn render(&self) -> Vec<N> {
-        vec![self.pub_state.render_with_perspective(Some(self.player))]
+        vec![
+            self.pub_state.render_with_perspective(
+                Some(self.player),
+                self.state.as_ref().map(|p| p.hand.as_slice()),
+            ),
+        ]
     }
 }
 
 impl PubState {
-    pub fn render_with_perspective(&self, perspective: Option<usize>) -> N {
-        N::Table(vec![
+    pub fn render_with_perspective(
+        &self,
+        perspective: Option<usize>,
+        hand: Option<&[Card]>,
+    ) -> N {
+        let mut rows: Vec<Row> = vec![
             vec![
                 (
                     A::Center,
@@ -62,7 +76,12 @@ impl PubState {
             ],
             vec![],
             vec![(A::Center, vec![self.render_casino_table()])],
-        ])
+        ];
+        if let Some(hand) = hand {
+            rows.push(vec![]);
+            rows.push(vec![(A::Center, render_hand(hand))]);
+        }
+        N::Table(rows)
     }
 
     pub fn render_player_table(&self, perspective: usize) -> N {
@@ -81,7 +100,6 @@ impl PubState {
         let p_len = self.players.len();
         for i in 0..p_len {
             let p = (perspective + i) % p_len;
-            let used = self.board.used_resources(p);
             rows.push(vec![
                 (A::Right, vec![N::Player(p)]),
                 (A::Left, vec![]),
@@ -89,12 +107,14 @@ impl PubState {
                 (A::Left, vec![]),
                 (
                     A::Center,
-                    vec![N::text(format!("{}", PLAYER_DICE - used.dice))],
+                    vec![N::text(format!("{}", self.board.remaining_dice(p)))],
                 ),
                 (A::Left, vec![]),
                 (
                     A::Center,
-                    vec![N::text(format!("{}", PLAYER_OWNER_TOKENS - used.tokens))],
+                    vec![
+                        N::text(format!("{}", self.board.remaining_owner_tokens(p))),
+                    ],
                 ),
                 (A::Left, vec![]),
                 (
@@ -114,22 +134,19 @@ impl PubState {
             casino_names.push((A::Left, vec![N::text("  ")]));
             casino_names.push((A::Center, vec![casino.render()]));
             remaining_cards.push((A::Left, vec![]));
-            remaining_cards.push((
-                A::Center,
-                vec![
-                    N::text(format!(
-                        "{}",
-                        CASINO_CARDS - casino_card_count(&self.played, casino)
-                    )),
-                ],
-            ));
+            let cards_left = self.casino_cards_left
+                .iter()
+                .find(|&&(c, _)| c == *casino)
+                .map(|&(_, n)| n)
+                .unwrap_or(0);
+            remaining_cards.push((A::Center, vec![N::text(format!("{}", cards_left))]));
             remaining_tiles.push((A::Left, vec![]));
             remaining_tiles.push((
                 A::Center,
                 vec![
                     N::text(format!(
                         "{}",
-                        CASINO_TILES - self.board.casino_tile_count(casino)
+                        self.board.remaining_casino_tiles(casino)
                     )),
                 ],
             ));
@@ -138,24 +155,51 @@ impl PubState {
     }
 }
 
+const BLOCK_FULL_WIDTH: usize = TILE_WIDTH * BLOCK_WIDTH;
+
 fn block_offset(block: &Block) -> (usize, usize) {
     (
-        match *block {
-            Block::A | Block::C | Block::E => 0,
-            Block::B | Block::D | Block::F => TILE_WIDTH * 3 + STRIP_FULL_WIDTH,
-        },
-        match *block {
-            Block::A | Block::B => 0,
-            Block::C | Block::D => TILE_HEIGHT * 2 + ALLEY_FULL_HEIGHT,
-            Block::E => TILE_HEIGHT * 6 + ALLEY_FULL_HEIGHT * 2,
-            Block::F => TILE_HEIGHT * 5 + ALLEY_FULL_HEIGHT * 2,
+        match block.side() {
+            Side::Left => 0,
+            Side::Right => BLOCK_FULL_WIDTH + STRIP_FULL_WIDTH,
         },
+        TILE_HEIGHT * block.row() + ALLEY_FULL_HEIGHT * block.alleys(),
     )
 }
 
+/// The height of the board from the top of the first block to the bottom of the last.
+fn board_height() -> usize {
+    BLOCKS
+        .iter()
+        .map(|b| block_offset(b).1 + TILE_HEIGHT * b.rows())
+        .max()
+        .unwrap_or(0)
+}
+
+fn render_strip() -> N {
+    let height = board_height();
+    let fg = STRIP_BG.inv().mono();
+    let mut layers = vec![
+        (
+            0,
+            0,
+            vec![N::Bg(STRIP_BG.into(), vec![N::text(rect(STRIP_FULL_WIDTH, height))])],
+        ),
+    ];
+    let top = height.saturating_sub(STRIP_TEXT.len()) / 2;
+    for (i, c) in STRIP_TEXT.chars().enumerate() {
+        layers.push((
+            STRIP_FULL_WIDTH / 2,
+            top + i,
+            vec![N::Fg(fg.into(), vec![N::Bold(vec![N::text(c.to_string())])])],
+        ));
+    }
+    N::Canvas(layers)
+}
+
 impl Board {
     fn render(&self) -> N {
-        let mut layers = vec![];
+        let mut layers = vec![(BLOCK_FULL_WIDTH, 0, vec![render_strip()])];
         for block in BLOCKS {
             let (x, y) = block_offset(block);
             layers.push((x, y, vec![self.render_block(*block)]));
@@ -167,11 +211,9 @@ impl Board {
         let mut layers = vec![];
         for lot in 1..block.max_lot() + 1 {
             let loc = Loc { block, lot };
-            let x = (lot - 1) % 3;
-            let y = (lot - 1) / 3;
             layers.push((
-                x * TILE_WIDTH,
-                y * TILE_HEIGHT,
+                (loc.col() - 1) * TILE_WIDTH,
+                (loc.row() - block.row()) * TILE_HEIGHT,
                 vec![self.get(&loc).render(&loc)],
             ));
         }
@@ -181,7 +223,12 @@ impl Board {
 
 impl BoardTile {
     fn render(&self, loc: &Loc) -> N {
-        let bot_text = format!("{}{:2}", loc.block, loc.lot);
+        let bot_text = match *self {
+            BoardTile::Built { height, .. } if height > CASINO_DEFAULT_HEIGHT => {
+                format!("{}{:2} ^{}", loc.block, loc.lot, height)
+            }
+            _ => format!("{}{:2}", loc.block, loc.lot),
+        };
         let player_color: Col = match *self {
             BoardTile::Owned { player }
             | BoardTile::Built {
@@ -296,3 +343,14 @@ pub fn render_cash(amount: usize) -> N {
         N::Fg(GREEN.into(), vec![N::text(format!("${}", amount))]),
     ])
 }
+
+fn render_hand(hand: &[Card]) -> Vec<N> {
+    vec![
+        N::Bold(vec![N::text("Your cards: ")]),
+        if hand.is_empty() {
+            N::text("none")
+        } else {
+            N::Group(render_cards(hand))
+        },
+    ]
+}
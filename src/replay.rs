@@ -0,0 +1,409 @@
+use serde_json;
+
+use brdgme_game::Gamer;
+use brdgme_game::errors::GameError;
+
+use std::fmt;
+
+use {Game, Phase, DIE_MAX, DIE_MIN};
+use board::Board;
+use card::Card;
+use dice::{DiceSource, PhysicalDice, ScriptedDice};
+use rng::fnv1a;
+use stats::PlayerStats;
+
+/// A recorded game which can be fed back through `Gamer::command` to reproduce the final state.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub players: usize,
+    pub commands: Vec<ReplayCommand>,
+    /// The dice used instead of fair dice from the seed.
+    #[serde(default)]
+    pub dice: Option<ReplayDice>,
+    /// The hash of the final state, as given by `state_hash`.
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+/// The dice a replay is played with when it doesn't roll fair dice from the seed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ReplayDice {
+    /// Values rolled in order. Actions needing more dice than are left fail like any other
+    /// invalid command, and boss ties which run out fail with `ReplayError::DiceExhausted`.
+    Scripted(Vec<usize>),
+    /// Physical dice, whose values are entered by roll commands recorded in the replay.
+    Physical,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayCommand {
+    pub player: usize,
+    pub input: String,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Setup(GameError),
+    Command {
+        index: usize,
+        command: ReplayCommand,
+        error: GameError,
+    },
+    /// A command left boss ties waiting for dice which the replay can't enter, as its dice
+    /// aren't physical.
+    DiceExhausted { index: usize, command: ReplayCommand },
+    MissingHash { actual: String },
+    HashMismatch { expected: String, actual: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Setup(ref e) => write!(f, "could not create game: {}", e),
+            ReplayError::Command {
+                index,
+                ref command,
+                ref error,
+            } => write!(
+                f,
+                "command {} (player {}: \"{}\") failed: {}",
+                index, command.player, command.input, error
+            ),
+            ReplayError::DiceExhausted {
+                index,
+                ref command,
+            } => write!(
+                f,
+                "command {} (player {}: \"{}\") ran out of dice",
+                index, command.player, command.input
+            ),
+            ReplayError::MissingHash { ref actual } => {
+                write!(f, "replay has no hash, final state hash is {}", actual)
+            }
+            ReplayError::HashMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "final state hash {} doesn't match expected {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, players: usize) -> Self {
+        Replay {
+            seed,
+            players,
+            commands: vec![],
+            dice: None,
+            hash: None,
+        }
+    }
+
+    /// An empty replay starting from the same state as a game, using the seed it was created
+    /// with. The game's commands from the start then need to be recorded or added to it.
+    pub fn for_game(game: &Game) -> Self {
+        let dice = match game.dice {
+            DiceSource::Fair(_) => None,
+            DiceSource::Scripted(ref d) => Some(ReplayDice::Scripted(d.values().to_vec())),
+            DiceSource::Physical(_) => Some(ReplayDice::Physical),
+        };
+        Replay {
+            dice,
+            ..Replay::new(game.seed, game.players.len())
+        }
+    }
+
+    /// A replay which rolls the given values in order instead of using fair dice.
+    pub fn scripted(seed: u64, players: usize, dice: Vec<usize>) -> Self {
+        Replay {
+            dice: Some(ReplayDice::Scripted(dice)),
+            ..Replay::new(seed, players)
+        }
+    }
+
+    /// A replay whose dice are entered with roll commands instead of rolled from the seed.
+    pub fn physical(seed: u64, players: usize) -> Self {
+        Replay {
+            dice: Some(ReplayDice::Physical),
+            ..Replay::new(seed, players)
+        }
+    }
+
+    /// Creates the game the replay starts from, which is also the game to record commands
+    /// against.
+    pub fn start(&self) -> Result<Game, ReplayError> {
+        let (mut game, _) = Game::with_seed(self.players, self.seed).map_err(ReplayError::Setup)?;
+        match self.dice {
+            Some(ReplayDice::Scripted(ref values)) => {
+                if let Some(v) = values.iter().find(|&&v| v < DIE_MIN || v > DIE_MAX) {
+                    return Err(ReplayError::Setup(GameError::InvalidInput {
+                        message: format!("{} isn't a valid die value", v),
+                    }));
+                }
+                game.dice = DiceSource::Scripted(ScriptedDice::new(values.clone()));
+            }
+            Some(ReplayDice::Physical) => {
+                game.dice = DiceSource::Physical(PhysicalDice::default());
+            }
+            None => {}
+        }
+        Ok(game)
+    }
+
+    /// Runs a command against the game and records it if it succeeds.
+    pub fn record(
+        &mut self,
+        game: &mut Game,
+        player: usize,
+        input: &str,
+    ) -> Result<(), GameError> {
+        game.command(player, input, &player_names(self.players))?;
+        self.commands.push(ReplayCommand {
+            player,
+            input: input.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Stores the hash of the final state so later runs can be verified against it.
+    pub fn seal(&mut self, game: &Game) {
+        self.hash = Some(state_hash(game));
+    }
+
+    /// Plays the replay from the start, returning the final state.
+    pub fn run(&self) -> Result<Game, ReplayError> {
+        let mut game = self.start()?;
+        let names = player_names(self.players);
+        for (index, command) in self.commands.iter().enumerate() {
+            game.command(command.player, &command.input, &names)
+                .map_err(|error| ReplayError::Command {
+                    index,
+                    command: command.clone(),
+                    error,
+                })?;
+            if game.phase == Phase::AwaitingDice && self.dice != Some(ReplayDice::Physical) {
+                return Err(ReplayError::DiceExhausted {
+                    index,
+                    command: command.clone(),
+                });
+            }
+        }
+        Ok(game)
+    }
+
+    /// Plays the replay and checks the final state matches the stored hash.
+    pub fn verify(&self) -> Result<Game, ReplayError> {
+        let game = self.run()?;
+        let actual = state_hash(&game);
+        match self.hash {
+            None => Err(ReplayError::MissingHash { actual }),
+            Some(ref expected) if *expected != actual => Err(ReplayError::HashMismatch {
+                expected: expected.to_owned(),
+                actual,
+            }),
+            Some(_) => Ok(game),
+        }
+    }
+}
+
+fn player_names(players: usize) -> Vec<String> {
+    (0..players).map(|p| format!("player{}", p + 1)).collect()
+}
+
+/// The parts of a player which affect play, along with their stats so a replay which miscounts
+/// them doesn't verify.
+#[derive(Serialize)]
+struct PlayerProjection<'a> {
+    cash: usize,
+    points: usize,
+    hand: &'a [Card],
+    stats: &'a PlayerStats,
+}
+
+/// The parts of a game which affect play, so the hash doesn't change when bookkeeping such as
+/// the save version or the dice source is added to or reorganised.
+#[derive(Serialize)]
+struct GameProjection<'a> {
+    board: &'a Board,
+    players: Vec<PlayerProjection<'a>>,
+    deck: &'a [Card],
+    played: &'a [Card],
+    current_player: usize,
+    phase: Phase,
+    gambled: bool,
+    finished: bool,
+}
+
+/// A stable FNV-1a hash of the gameplay state, formatted as hex.
+pub fn state_hash(game: &Game) -> String {
+    let projection = GameProjection {
+        board: &game.board,
+        players: game.players
+            .iter()
+            .map(|p| PlayerProjection {
+                cash: p.cash,
+                points: p.points,
+                hand: &p.hand,
+                stats: &p.stats,
+            })
+            .collect(),
+        deck: &game.deck,
+        played: &game.played,
+        current_player: game.current_player,
+        phase: game.phase,
+        gambled: game.gambled,
+        finished: game.finished,
+    };
+    let json = serde_json::to_string(&projection).expect("could not serialise game");
+    format!("{:016x}", fnv1a(json.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Command;
+    use dice::Dice;
+
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn recorded() -> Replay {
+        let mut replay = Replay::new(99, 3);
+        let mut game = replay.start().expect("could not create game");
+        for _ in 0..6 {
+            let p = game.current_player;
+            replay
+                .record(&mut game, p, "done")
+                .expect("could not end turn");
+            assert!(game.validate().is_empty());
+        }
+        replay.seal(&game);
+        replay
+    }
+
+    #[test]
+    fn replay_verifies() {
+        let replay = recorded();
+        let json = serde_json::to_string(&replay).expect("could not serialise replay");
+        let loaded: Replay = serde_json::from_str(&json).expect("could not deserialise replay");
+        loaded.verify().expect("expected replay to verify");
+    }
+
+    #[test]
+    fn replay_from_new_game_verifies() {
+        let (mut game, _) = Game::new(2).expect("could not create game");
+        let mut replay = Replay::for_game(&game);
+        for _ in 0..4 {
+            let p = game.current_player;
+            replay
+                .record(&mut game, p, "done")
+                .expect("could not end turn");
+        }
+        replay.seal(&game);
+        replay.verify().expect("expected replay to verify");
+    }
+
+    #[test]
+    fn replay_detects_changes() {
+        let mut replay = recorded();
+        replay.commands.pop();
+        match replay.verify() {
+            Err(ReplayError::HashMismatch { .. }) => {}
+            _ => panic!("expected hash mismatch"),
+        }
+    }
+
+    #[test]
+    fn hash_ignores_bookkeeping() {
+        let (mut game, _) = Game::with_seed(3, 99).expect("could not create game");
+        let hash = state_hash(&game);
+        game.version += 1;
+        game.dice = DiceSource::Scripted(ScriptedDice::new(vec![1]));
+        assert_eq!(hash, state_hash(&game));
+        game.players[0].cash += 1;
+        assert_ne!(hash, state_hash(&game));
+    }
+
+    #[test]
+    fn hash_covers_stats() {
+        let (mut game, _) = Game::with_seed(3, 99).expect("could not create game");
+        let hash = state_hash(&game);
+        game.players[0].stats.dice_rolled += 1;
+        assert_ne!(hash, state_hash(&game));
+    }
+
+    #[test]
+    fn scripted_replay_verifies() {
+        let mut replay = Replay::scripted(7, 2, vec![3; 10]);
+        let mut game = replay.start().expect("could not create game");
+        for _ in 0..4 {
+            let p = game.current_player;
+            replay
+                .record(&mut game, p, "done")
+                .expect("could not end turn");
+        }
+        replay.seal(&game);
+        let json = serde_json::to_string(&replay).expect("could not serialise replay");
+        let loaded: Replay = serde_json::from_str(&json).expect("could not deserialise replay");
+        match loaded.verify().expect("expected replay to verify").dice {
+            DiceSource::Scripted(_) => {}
+            d => panic!("expected scripted dice, got {:?}", d),
+        }
+    }
+
+    #[test]
+    fn physical_replay_verifies() {
+        let mut replay = Replay::physical(7, 2);
+        let mut game = replay.start().expect("could not create game");
+        let p = game.current_player;
+        for input in &["roll 4", "roll 5", "done"] {
+            replay
+                .record(&mut game, p, input)
+                .expect("could not run command");
+        }
+        replay.seal(&game);
+        assert_eq!(Replay::for_game(&game).dice, Some(ReplayDice::Physical));
+        match replay.verify().expect("expected replay to verify").dice {
+            DiceSource::Physical(ref d) => assert_eq!(d.available(), Some(2)),
+            ref d => panic!("expected physical dice, got {:?}", d),
+        }
+    }
+
+    #[test]
+    fn running_out_of_dice_fails() {
+        let mut replay = Replay::new(5, 3);
+        let mut game = replay.start().expect("could not create game");
+        let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+        let mut actions = 0;
+        while !game.finished {
+            let p = game.current_player;
+            let legal = game.legal_commands(p);
+            let command = if actions >= 3 && legal.contains(&Command::Done) {
+                Command::Done
+            } else {
+                rng.choose(&legal).expect("expected a legal command").clone()
+            };
+            actions = if command == Command::Done { 0 } else { actions + 1 };
+            replay
+                .record(&mut game, p, &command.to_string())
+                .expect("legal command failed");
+        }
+        replay.dice = Some(ReplayDice::Scripted(vec![]));
+        match replay.run() {
+            Err(ReplayError::Command { .. }) | Err(ReplayError::DiceExhausted { .. }) => {}
+            r => panic!("expected running out of dice to fail, got {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn replay_checks_dice() {
+        match Replay::scripted(7, 2, vec![0]).start() {
+            Err(ReplayError::Setup(_)) => {}
+            _ => panic!("expected invalid dice to fail"),
+        }
+    }
+}
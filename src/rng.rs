@@ -0,0 +1,133 @@
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use std::fmt;
+
+/// A seeded `XorShiftRng` which is serialised with the game so every random decision can be
+/// reproduced. The generator's state is private, so the seed and the number of values drawn are
+/// saved instead, and the generator is rebuilt from them the first time it is used after loading.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameRng {
+    seed: [u32; 4],
+    draws: u64,
+    #[serde(skip)]
+    rng: Option<XorShiftRng>,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng {
+            seed: seed_words(seed),
+            draws: 0,
+            rng: None,
+        }
+    }
+}
+
+/// Expands a seed into the four words `XorShiftRng` is seeded with. The seed is expanded using
+/// splitmix64 so similar seeds give unrelated generators, and so the words are never all zeroes,
+/// which `XorShiftRng` doesn't allow.
+pub fn seed_words(seed: u64) -> [u32; 4] {
+    let mut x = seed;
+    let mut next = || {
+        x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    let a = next();
+    let b = next();
+    [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32 | 1]
+}
+
+/// Hashes bytes with 64 bit FNV-1a, which is stable across platforms and Rust versions so it can
+/// be used to derive seeds and fingerprints which are saved.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_seed(0)
+    }
+}
+
+impl PartialEq for GameRng {
+    fn eq(&self, other: &GameRng) -> bool {
+        self.seed == other.seed && self.draws == other.draws
+    }
+}
+
+impl fmt::Debug for GameRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GameRng {{ seed: {:?}, draws: {} }}", self.seed, self.draws)
+    }
+}
+
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        if self.rng.is_none() {
+            let mut rng = XorShiftRng::from_seed(self.seed);
+            for _ in 0..self.draws {
+                rng.next_u32();
+            }
+            self.rng = Some(rng);
+        }
+        self.draws += 1;
+        self.rng
+            .as_mut()
+            .expect("rng should have been built")
+            .next_u32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = GameRng::from_seed(42);
+        let mut b = GameRng::from_seed(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn different_seed_different_sequence() {
+        let mut a = GameRng::from_seed(1);
+        let mut b = GameRng::from_seed(2);
+        assert!((0..10).any(|_| a.next_u32() != b.next_u32()));
+    }
+
+    #[test]
+    fn close_seeds_start_differently() {
+        for seed in 0..10 {
+            let mut a = GameRng::from_seed(seed);
+            let mut b = GameRng::from_seed(seed + 1);
+            assert_ne!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn loaded_rng_continues_sequence() {
+        let mut a = GameRng::from_seed(7);
+        for _ in 0..10 {
+            a.next_u32();
+        }
+        let json = serde_json::to_string(&a).expect("could not serialise rng");
+        let mut b: GameRng = serde_json::from_str(&json).expect("could not deserialise rng");
+        assert_eq!(a, b);
+        for _ in 0..10 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+}
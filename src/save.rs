@@ -0,0 +1,256 @@
+use serde::{Deserialize, Deserializer};
+use serde::de::Error as DeError;
+use serde_json::{self, Map, Value};
+
+use brdgme_game::errors::GameError;
+
+use {Game, Phase, Player};
+use board::Board;
+use card::Card;
+use dice::DiceSource;
+use rng::{self, fnv1a};
+use stats::PlayerStats;
+
+/// The layout version written into every new save. Bump this and add a migration whenever a
+/// change to `Game` or anything it contains would stop older saves from loading, and add a
+/// fixture for the new version.
+pub const SAVE_VERSION: usize = 1;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), GameError>;
+
+/// Migrations from each version to the next, so `MIGRATIONS[0]` upgrades a version 0 save to
+/// version 1. Migrations write the layout of the version they upgrade to as plain JSON rather
+/// than using the current types, so they keep working as the types change.
+static MIGRATIONS: &'static [Migration] = &[v0_to_v1];
+
+/// The current save layout, which every save is migrated to before it becomes a `Game`.
+#[derive(Deserialize)]
+struct GameData {
+    version: usize,
+    seed: u64,
+    players: Vec<Player>,
+    current_player: usize,
+    deck: Vec<Card>,
+    played: Vec<Card>,
+    board: Board,
+    finished: bool,
+    phase: Phase,
+    gambled: bool,
+    dice: DiceSource,
+}
+
+/// Games are migrated as they are deserialised, so every way of loading a game, including the
+/// `Gamer` command line and server, can read saves from older versions.
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let value = migrate(value).map_err(|e| D::Error::custom(e.to_string()))?;
+        let data = GameData::deserialize(value).map_err(D::Error::custom)?;
+        Ok(Game {
+            version: data.version,
+            seed: data.seed,
+            players: data.players,
+            current_player: data.current_player,
+            deck: data.deck,
+            played: data.played,
+            board: data.board,
+            finished: data.finished,
+            phase: data.phase,
+            gambled: data.gambled,
+            dice: data.dice,
+            events: vec![],
+        })
+    }
+}
+
+fn invalid(message: &str) -> GameError {
+    GameError::InvalidInput {
+        message: format!("invalid save: {}", message),
+    }
+}
+
+fn to_value<T: ::serde::Serialize>(v: &T) -> Result<Value, GameError> {
+    serde_json::to_value(v).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Derives a seed from the contents of a save, so migrated saves which need new randomness each
+/// get their own dice, and loading the same save twice gives the same game.
+fn content_seed(obj: &Map<String, Value>) -> Result<u64, GameError> {
+    let json = serde_json::to_string(obj).map_err(|e| invalid(&e.to_string()))?;
+    Ok(fnv1a(json.as_bytes()))
+}
+
+/// Upgrades a saved game to the current layout. Saves without a version predate versioning and
+/// are treated as version 0.
+pub fn migrate(mut value: Value) -> Result<Value, GameError> {
+    {
+        let obj = value
+            .as_object_mut()
+            .ok_or_else(|| invalid("expected an object"))?;
+        let version = match obj.get("version") {
+            None => 0,
+            Some(v) => v.as_u64().ok_or_else(|| invalid("version must be a number"))? as usize,
+        };
+        if version > SAVE_VERSION {
+            return Err(invalid(&format!(
+                "version {} is newer than the supported version {}",
+                version, SAVE_VERSION
+            )));
+        }
+        for migration in &MIGRATIONS[version..] {
+            migration(obj)?;
+        }
+        obj.insert("version".to_string(), Value::from(SAVE_VERSION));
+    }
+    Ok(value)
+}
+
+/// Loads a saved game from JSON, upgrading it from older versions if needed.
+pub fn load(json: &str) -> Result<Game, GameError> {
+    serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Version 1 added the creation seed, the turn phase, seeded dice, and player stats and hands.
+/// Version 0 games were always waiting on actions, and their dealt cards were put straight into
+/// `played` so hands start empty. The real seed of a version 0 game is lost, so the seed and dice
+/// are derived from the save so each migrated game rolls differently.
+fn v0_to_v1(obj: &mut Map<String, Value>) -> Result<(), GameError> {
+    let seed = content_seed(obj)?;
+    let stats = to_value(&PlayerStats::default())?;
+    for player in obj.get_mut("players")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| invalid("expected a list of players"))?
+    {
+        let player = player
+            .as_object_mut()
+            .ok_or_else(|| invalid("expected player to be an object"))?;
+        player.entry("stats").or_insert_with(|| stats.clone());
+        player.entry("hand").or_insert_with(|| Value::Array(vec![]));
+    }
+    let mut rng = Map::new();
+    rng.insert(
+        "seed".to_string(),
+        Value::from(rng::seed_words(seed).to_vec()),
+    );
+    rng.insert("draws".to_string(), Value::from(0));
+    let mut fair = Map::new();
+    fair.insert("rng".to_string(), Value::Object(rng));
+    let mut dice = Map::new();
+    dice.insert("Fair".to_string(), Value::Object(fair));
+    obj.entry("seed").or_insert_with(|| Value::from(seed));
+    obj.entry("phase").or_insert_with(|| Value::from("Actions"));
+    obj.entry("gambled").or_insert(Value::Bool(false));
+    obj.entry("dice").or_insert(Value::Object(dice));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::{Block, BoardTile, Loc};
+
+    static FIXTURES: &'static [&'static str] = &[
+        include_str!("fixtures/v0.json"),
+        include_str!("fixtures/v1.json"),
+    ];
+
+    #[test]
+    fn fixtures_cover_every_version() {
+        assert_eq!(SAVE_VERSION + 1, FIXTURES.len());
+    }
+
+    #[test]
+    fn fixtures_load() {
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let game = load(fixture)
+                .unwrap_or_else(|e| panic!("could not load version {} fixture: {}", version, e));
+            assert_eq!(SAVE_VERSION, game.version);
+            assert_eq!(Phase::Actions, game.phase);
+            assert_eq!(2, game.players.len());
+            assert_eq!(1, game.current_player);
+            assert_eq!(Some(&Card::GameEnd), game.deck.get(3));
+            match game.board.get(&Loc {
+                block: Block::C,
+                lot: 3,
+            }) {
+                BoardTile::Owned { player: 1 } => {}
+                t => panic!("expected C3 to be owned by player 1, got {:?}", t),
+            }
+        }
+    }
+
+    #[test]
+    fn v0_migrates() {
+        let game = load(FIXTURES[0]).expect("could not load version 0 fixture");
+        assert!(game.players.iter().all(|p| p.hand.is_empty()));
+        assert_eq!(4, game.played.len());
+    }
+
+    #[test]
+    fn migrated_dice_are_seeded_from_the_save() {
+        let mut other: Value = serde_json::from_str(FIXTURES[0]).unwrap();
+        other["players"][0]["cash"] = Value::from(15);
+        let a = load(FIXTURES[0]).expect("could not load version 0 fixture");
+        let b: Game = serde_json::from_value(other).expect("could not load game");
+        assert_ne!(DiceSource::default(), a.dice);
+        assert_ne!(a.dice, b.dice);
+        assert_eq!(a.dice, load(FIXTURES[0]).unwrap().dice);
+    }
+
+    #[test]
+    fn serde_migrates_old_saves() {
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let game: Game = serde_json::from_str(fixture)
+                .unwrap_or_else(|e| panic!("could not deserialise version {}: {}", version, e));
+            assert_eq!(SAVE_VERSION, game.version);
+        }
+    }
+
+    #[test]
+    fn current_layout_matches_fixture() {
+        let fixture: Value = serde_json::from_str(FIXTURES[SAVE_VERSION]).unwrap();
+        let game: Game = serde_json::from_value(fixture.clone()).expect("could not load fixture");
+        assert_eq!(fixture, to_value(&game).unwrap());
+
+        let (game, _) = Game::with_seed(2, 5).expect("could not create game");
+        let new = to_value(&game).unwrap();
+        let keys = |v: &Value| {
+            v.as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(keys(&fixture), keys(&new));
+        assert_eq!(keys(&fixture["players"][0]), keys(&new["players"][0]));
+        assert_eq!(
+            keys(&fixture["players"][0]["stats"]),
+            keys(&new["players"][0]["stats"])
+        );
+    }
+
+    #[test]
+    fn saves_keep_the_seed() {
+        let (game, _) = Game::with_seed(3, 5).expect("could not create game");
+        let json = serde_json::to_string(&game).expect("could not serialise game");
+        assert_eq!(5, load(&json).expect("could not load game").seed);
+    }
+
+    #[test]
+    fn saves_round_trip() {
+        let (game, _) = Game::with_seed(3, 5).expect("could not create game");
+        let json = serde_json::to_string(&game).expect("could not serialise game");
+        let loaded = load(&json).expect("could not load game");
+        assert_eq!(json, serde_json::to_string(&loaded).expect("could not serialise game"));
+    }
+
+    #[test]
+    fn newer_versions_fail() {
+        let mut value: Value = serde_json::from_str(FIXTURES[SAVE_VERSION]).unwrap();
+        value["version"] = Value::from(SAVE_VERSION + 1);
+        assert!(migrate(value).is_err());
+    }
+}
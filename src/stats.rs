@@ -0,0 +1,49 @@
+use brdgme_game::Stat;
+
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct PlayerStats {
+    pub payout_cash: usize,
+    pub gamble_won: usize,
+    pub gamble_lost: usize,
+    pub tiles_built: usize,
+    pub tiles_sprawled: usize,
+    pub dice_rolled: usize,
+    pub biggest_casino: usize,
+}
+
+impl PlayerStats {
+    pub fn to_stats(&self) -> HashMap<String, Stat> {
+        let mut stats: HashMap<String, Stat> = HashMap::new();
+        stats.insert(
+            "Cash from payouts".to_string(),
+            Stat::Int(self.payout_cash as i32),
+        );
+        stats.insert(
+            "Cash won gambling".to_string(),
+            Stat::Int(self.gamble_won as i32),
+        );
+        stats.insert(
+            "Cash lost gambling".to_string(),
+            Stat::Int(self.gamble_lost as i32),
+        );
+        stats.insert(
+            "Tiles built".to_string(),
+            Stat::Int(self.tiles_built as i32),
+        );
+        stats.insert(
+            "Tiles sprawled".to_string(),
+            Stat::Int(self.tiles_sprawled as i32),
+        );
+        stats.insert(
+            "Dice rolled".to_string(),
+            Stat::Int(self.dice_rolled as i32),
+        );
+        stats.insert(
+            "Biggest casino".to_string(),
+            Stat::Int(self.biggest_casino as i32),
+        );
+        stats
+    }
+}
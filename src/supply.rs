@@ -0,0 +1,43 @@
+use brdgme_game::errors::GameError;
+
+use std::fmt;
+
+use casino::Casino;
+
+/// Returned when an action needs more of a limited supply than is left.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SupplyError {
+    Dice { player: usize },
+    OwnerTokens { player: usize },
+    CasinoTiles {
+        casino: Casino,
+        needed: usize,
+        remaining: usize,
+    },
+}
+
+impl fmt::Display for SupplyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SupplyError::Dice { .. } => write!(f, "you don't have any dice left"),
+            SupplyError::OwnerTokens { .. } => write!(f, "you don't have any owner tokens left"),
+            SupplyError::CasinoTiles {
+                casino,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "{} {} tiles are needed but only {} are left",
+                needed, casino, remaining
+            ),
+        }
+    }
+}
+
+impl From<SupplyError> for GameError {
+    fn from(e: SupplyError) -> Self {
+        GameError::InvalidInput {
+            message: e.to_string(),
+        }
+    }
+}
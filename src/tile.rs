@@ -1,13 +1,18 @@
+use serde_json;
+
 use std::collections::HashMap;
 
-use board::{Block, Loc};
-use casino::Casino;
+use board::{Block, Loc, Lot, Side, BLOCKS, BLOCK_WIDTH};
+use casino::{Casino, CASINOS};
+use {CASINO_CARDS, DIE_MAX, DIE_MIN};
 
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum Payout {
     Casino(Casino),
     Strip,
 }
 
+#[derive(Deserialize, Debug, Clone)]
 pub struct Tile {
     pub payout: Payout,
     pub starting_cash: usize,
@@ -18,496 +23,186 @@ pub struct Tile {
 
 type TileMap = HashMap<Loc, Tile>;
 
+/// The board definition embedded in the game, which can be edited to fix errata or try other maps
+/// without touching any code.
+static LAYOUT_JSON: &'static str = include_str!("layout.json");
+
 lazy_static! {
-    pub static ref TILES: TileMap = tiles();
+    pub static ref LAYOUT: Layout = Layout::from_json(LAYOUT_JSON)
+        .unwrap_or_else(|e| panic!("invalid board layout: {}", e));
+    pub static ref TILES: TileMap = LAYOUT.tiles.clone();
+}
+
+/// Where a block sits on the board. Rows count tile rows down from the top of the board, and
+/// alleys count the alleys crossed to get there, so blocks in the same row on opposite sides of
+/// the Strip face each other.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct BlockLayout {
+    pub block: Block,
+    pub lots: Lot,
+    pub side: Side,
+    pub row: usize,
+    pub alleys: usize,
+}
+
+impl BlockLayout {
+    /// The number of tile rows in the block.
+    pub fn rows(&self) -> usize {
+        self.lots / BLOCK_WIDTH
+    }
 }
 
-fn tiles() -> TileMap {
-    let mut map: TileMap = HashMap::new();
-    map.insert(
-        (Block::A, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::A, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::A, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::A, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::A, 5).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::A, 6).into(),
-        Tile {
-            payout: Payout::Strip,
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
+#[derive(Deserialize)]
+struct TileDef {
+    loc: Loc,
+    payout: Payout,
+    starting_cash: usize,
+    die: usize,
+    build_cost: usize,
+    strip: bool,
+}
 
-    map.insert(
-        (Block::B, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::B, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::B, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::B, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::B, 5).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::B, 6).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
+#[derive(Deserialize)]
+struct LayoutDef {
+    blocks: Vec<BlockLayout>,
+    tiles: Vec<TileDef>,
+}
+
+/// The blocks and tiles which make up the board.
+pub struct Layout {
+    /// The layout of each block, in `BLOCKS` order.
+    blocks: Vec<BlockLayout>,
+    /// The index of the first lot of each block, in `BLOCKS` order.
+    block_offsets: Vec<usize>,
+    lot_count: usize,
+    pub tiles: TileMap,
+}
+
+impl Layout {
+    /// Parses and validates a board definition.
+    pub fn from_json(json: &str) -> Result<Layout, String> {
+        let def: LayoutDef = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut blocks: Vec<Option<BlockLayout>> = vec![None; BLOCKS.len()];
+        for b in def.blocks {
+            if b.lots == 0 || b.lots % BLOCK_WIDTH != 0 {
+                return Err(format!(
+                    "block {} has {} lots, which isn't a positive multiple of {}",
+                    b.block, b.lots, BLOCK_WIDTH
+                ));
+            }
+            if blocks[b.block as usize].is_some() {
+                return Err(format!("block {} is defined more than once", b.block));
+            }
+            blocks[b.block as usize] = Some(b);
+        }
+        let blocks = BLOCKS
+            .iter()
+            .map(|b| blocks[*b as usize].ok_or_else(|| format!("block {} is missing", b)))
+            .collect::<Result<Vec<BlockLayout>, String>>()?;
+        let block_offsets = blocks
+            .iter()
+            .scan(0, |offset, b| {
+                let start = *offset;
+                *offset += b.lots;
+                Some(start)
+            })
+            .collect();
+
+        let mut tiles: TileMap = HashMap::new();
+        for t in def.tiles {
+            let block = blocks[t.loc.block as usize];
+            if t.loc.lot < 1 || t.loc.lot > block.lots {
+                return Err(format!("tile {} isn't in its block", t.loc));
+            }
+            if t.strip != block.side.is_strip_lot(t.loc.lot) {
+                return Err(format!(
+                    "tile {} should {}be on the Strip",
+                    t.loc,
+                    if t.strip { "not " } else { "" }
+                ));
+            }
+            if t.die < DIE_MIN || t.die > DIE_MAX {
+                return Err(format!("tile {} has an invalid die of {}", t.loc, t.die));
+            }
+            let loc = t.loc;
+            let tile = Tile {
+                payout: t.payout,
+                starting_cash: t.starting_cash,
+                die: t.die,
+                build_cost: t.build_cost,
+                strip: t.strip,
+            };
+            if tiles.insert(loc, tile).is_some() {
+                return Err(format!("tile {} is defined more than once", loc));
+            }
+        }
+        for b in &blocks {
+            for lot in 1..b.lots + 1 {
+                if !tiles.contains_key(&(b.block, lot).into()) {
+                    return Err(format!("lot {}{} has no tile", b.block, lot));
+                }
+            }
+        }
+        for c in CASINOS {
+            let count = tiles
+                .values()
+                .filter(|t| t.payout == Payout::Casino(*c))
+                .count();
+            if count != CASINO_CARDS {
+                return Err(format!("{} has {} tiles, expected {}", c, count, CASINO_CARDS));
+            }
+        }
+
+        Ok(Layout {
+            lot_count: blocks.iter().map(|b| b.lots).sum(),
+            blocks,
+            block_offsets,
+            tiles,
+        })
+    }
+
+    pub fn block(&self, block: &Block) -> &BlockLayout {
+        &self.blocks[*block as usize]
+    }
+
+    pub fn lots(&self, block: &Block) -> Lot {
+        self.block(block).lots
+    }
+
+    pub fn offset(&self, block: &Block) -> usize {
+        self.block_offsets[*block as usize]
+    }
+
+    /// The number of lots on the board across all blocks.
+    pub fn lot_count(&self) -> usize {
+        self.lot_count
+    }
+}
 
-    map.insert(
-        (Block::C, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::C, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 5).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 9,
-            die: 1,
-            build_cost: 8,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 6).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::C, 7).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 8).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 9,
-            die: 1,
-            build_cost: 8,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 9).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::C, 10).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 11).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::C, 12).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    map.insert(
-        (Block::D, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::D, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::D, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::D, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::D, 5).into(),
-        Tile {
-            payout: Payout::Strip,
-            starting_cash: 9,
-            die: 1,
-            build_cost: 8,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::D, 6).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::D, 7).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::D, 8).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::D, 9).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
+    #[test]
+    fn embedded_layout_is_valid() {
+        Layout::from_json(LAYOUT_JSON).expect("expected embedded layout to be valid");
+        assert_eq!(48, TILES.len());
+    }
 
-    map.insert(
-        (Block::E, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::E, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::E, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::E, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::E, 5).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::E, 6).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
+    #[test]
+    fn layout_checks_strip() {
+        let json = LAYOUT_JSON.replacen(r#""strip": true"#, r#""strip": false"#, 1);
+        assert!(Layout::from_json(&json).is_err());
+    }
 
-    map.insert(
-        (Block::F, 1).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Albion),
-            starting_cash: 4,
-            die: 6,
-            build_cost: 20,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::F, 2).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::F, 3).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::F, 4).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Sphinx),
-            starting_cash: 6,
-            die: 4,
-            build_cost: 12,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::F, 5).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Pioneer),
-            starting_cash: 9,
-            die: 1,
-            build_cost: 8,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::F, 6).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::F, 7).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Vega),
-            starting_cash: 5,
-            die: 5,
-            build_cost: 15,
-            strip: true,
-        },
-    );
-    map.insert(
-        (Block::F, 8).into(),
-        Tile {
-            payout: Payout::Strip,
-            starting_cash: 8,
-            die: 2,
-            build_cost: 6,
-            strip: false,
-        },
-    );
-    map.insert(
-        (Block::F, 9).into(),
-        Tile {
-            payout: Payout::Casino(Casino::Tivoli),
-            starting_cash: 7,
-            die: 3,
-            build_cost: 9,
-            strip: false,
-        },
-    );
-    map
+    #[test]
+    fn layout_checks_casino_cards() {
+        let json = LAYOUT_JSON.replacen(
+            r#"{ "Casino": "Pioneer" }"#,
+            r#"{ "Casino": "Albion" }"#,
+            1,
+        );
+        assert!(Layout::from_json(&json).is_err());
+    }
 }
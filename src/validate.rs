@@ -0,0 +1,213 @@
+use std::fmt;
+
+use {Game, Phase};
+use board::{BoardTile, Loc, TileOwner};
+use card::Card;
+use casino::{Casino, CASINOS};
+use {CASINO_TILES, DIE_MAX, DIE_MIN, PLAYER_DICE, PLAYER_OWNER_TOKENS, POINT_STOPS};
+
+/// A broken invariant found by `Game::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    CurrentPlayerOutOfRange { current_player: usize, players: usize },
+    TooManyDice { player: usize, used: usize },
+    TooManyOwnerTokens { player: usize, used: usize },
+    UnknownPlayer { loc: Loc, player: usize },
+    InvalidDie { loc: Loc, die: usize },
+    TooManyCasinoTiles { casino: Casino, used: usize },
+    DuplicateCard { card: Card },
+    PointsOffTrack { player: usize, points: usize },
+    FinishedMismatch { finished: bool, phase: Phase },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::CurrentPlayerOutOfRange {
+                current_player,
+                players,
+            } => write!(
+                f,
+                "current player {} is out of range for {} players",
+                current_player, players
+            ),
+            Violation::TooManyDice { player, used } => write!(
+                f,
+                "player {} has {} dice on the board, the limit is {}",
+                player, used, PLAYER_DICE
+            ),
+            Violation::TooManyOwnerTokens { player, used } => write!(
+                f,
+                "player {} has {} owner tokens on the board, the limit is {}",
+                player, used, PLAYER_OWNER_TOKENS
+            ),
+            Violation::UnknownPlayer { loc, player } => {
+                write!(f, "{} belongs to unknown player {}", loc, player)
+            }
+            Violation::InvalidDie { loc, die } => {
+                write!(f, "{} has an invalid die of {}", loc, die)
+            }
+            Violation::TooManyCasinoTiles { casino, used } => write!(
+                f,
+                "{} has {} tiles on the board, the limit is {}",
+                casino, used, CASINO_TILES
+            ),
+            Violation::DuplicateCard { ref card } => {
+                write!(f, "{:?} is in more than one place", card)
+            }
+            Violation::PointsOffTrack { player, points } => write!(
+                f,
+                "player {} has points index {}, past the end of the track",
+                player, points
+            ),
+            Violation::FinishedMismatch { finished, phase } => write!(
+                f,
+                "game finished is {} but the phase is {:?}",
+                finished, phase
+            ),
+        }
+    }
+}
+
+impl Game {
+    /// Checks the game for broken invariants, such as supplies being overused or cards being in
+    /// more than one place. An empty list means the game is consistent.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = vec![];
+        let players = self.players.len();
+
+        if self.current_player >= players {
+            violations.push(Violation::CurrentPlayerOutOfRange {
+                current_player: self.current_player,
+                players,
+            });
+        }
+        if self.finished != (self.phase == Phase::GameOver) {
+            violations.push(Violation::FinishedMismatch {
+                finished: self.finished,
+                phase: self.phase,
+            });
+        }
+
+        for (p, player) in self.players.iter().enumerate() {
+            let used = self.board.used_resources(p);
+            if used.dice > PLAYER_DICE {
+                violations.push(Violation::TooManyDice {
+                    player: p,
+                    used: used.dice,
+                });
+            }
+            if used.tokens > PLAYER_OWNER_TOKENS {
+                violations.push(Violation::TooManyOwnerTokens {
+                    player: p,
+                    used: used.tokens,
+                });
+            }
+            if player.points >= POINT_STOPS.len() {
+                violations.push(Violation::PointsOffTrack {
+                    player: p,
+                    points: player.points,
+                });
+            }
+        }
+
+        for (loc, bt) in self.board.tiles() {
+            let player = match bt {
+                BoardTile::Unowned => continue,
+                BoardTile::Owned { player } => Some(player),
+                BoardTile::Built {
+                    owner: Some(TileOwner { player, die }),
+                    ..
+                } => {
+                    if die < DIE_MIN || die > DIE_MAX {
+                        violations.push(Violation::InvalidDie { loc, die });
+                    }
+                    Some(player)
+                }
+                BoardTile::Built { owner: None, .. } => None,
+            };
+            if let Some(player) = player {
+                if player >= players {
+                    violations.push(Violation::UnknownPlayer { loc, player });
+                }
+            }
+        }
+
+        for casino in CASINOS {
+            let used = self.board.casino_tile_count(casino);
+            if used > CASINO_TILES {
+                violations.push(Violation::TooManyCasinoTiles {
+                    casino: *casino,
+                    used,
+                });
+            }
+        }
+
+        let mut seen: Vec<&Card> = vec![];
+        let held = self.players.iter().flat_map(|p| p.hand.iter());
+        for card in self.deck.iter().chain(self.played.iter()).chain(held) {
+            if seen.contains(&card) {
+                violations.push(Violation::DuplicateCard { card: card.clone() });
+            } else {
+                seen.push(card);
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use board::Block;
+    use Player;
+
+    #[test]
+    fn new_game_is_valid() {
+        let (game, _) = Game::with_seed(4, 3).expect("could not create game");
+        assert_eq!(Vec::<Violation>::new(), game.validate());
+    }
+
+    #[test]
+    fn validate_finds_violations() {
+        let mut game = Game {
+            players: vec![Player::default(); 2],
+            current_player: 2,
+            ..Game::default()
+        };
+        let card = Card::Loc {
+            loc: (Block::A, 1).into(),
+        };
+        game.deck.push(card.clone());
+        game.played.push(card.clone());
+        let held = Card::Loc {
+            loc: (Block::B, 1).into(),
+        };
+        game.players[0].hand.push(held.clone());
+        game.players[1].hand.push(held.clone());
+        for lot in 1..CASINO_TILES + 2 {
+            let loc: Loc = (Block::C, lot).into();
+            game.board.set(
+                loc,
+                BoardTile::Built {
+                    casino: Casino::Vega,
+                    owner: Some(TileOwner { player: 0, die: 1 }),
+                    height: 1,
+                },
+            );
+        }
+        let violations = game.validate();
+        assert!(violations.contains(&Violation::CurrentPlayerOutOfRange {
+            current_player: 2,
+            players: 2,
+        }));
+        assert!(violations.contains(&Violation::DuplicateCard { card }));
+        assert!(violations.contains(&Violation::DuplicateCard { card: held }));
+        assert!(violations.contains(&Violation::TooManyCasinoTiles {
+            casino: Casino::Vega,
+            used: CASINO_TILES + 1,
+        }));
+    }
+}